@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CatalogVersion } from "./CatalogVersion";
+
+/**
+ * Single page of the versions matching the query
+ */
+export type CatalogPage = { versions: Array<CatalogVersion>, 
+/**
+ * Count of the versions matching the query on all pages
+ */
+total: number, 
+/**
+ * Starts from 1
+ */
+page: number, per_page: number, pages: number, };
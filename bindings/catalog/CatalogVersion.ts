@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReleaseType } from "./ReleaseType";
+
+export type CatalogVersion = { id: string, 
+/**
+ * Version manifest
+ */
+url: string, release_type: ReleaseType, release_time: string | null, sha1: string | null, };
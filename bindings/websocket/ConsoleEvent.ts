@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConsoleStream } from "./ConsoleStream";
+
+export type ConsoleEvent = { "type": "started", "details": { instance_id: bigint, pid: number, } } | { "type": "line", "details": { instance_id: bigint, stream: ConsoleStream, line: string, } } | { "type": "exited", "details": { instance_id: bigint, code: number | null, signal: number | null, } };
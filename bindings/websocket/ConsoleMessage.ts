@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BaseMessage } from "./BaseMessage";
+import type { ConsoleEvent } from "./ConsoleEvent";
+
+export type ConsoleMessage = { base: BaseMessage, data: ConsoleEvent, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Kind of the failure, so the client can react without parsing messages
+ */
+export type ErrorCode = "network" | "http" | "checksum" | "disk" | "database" | "manifest_parse" | "version_not_available" | "unsupported_os" | "unsupported_arch" | "invalid_request" | "not_found" | "launch" | "cancelled" | "internal";
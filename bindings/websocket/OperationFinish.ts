@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { OperationStatus } from "./OperationStatus";
+import type { StageError } from "./StageError";
 
-export type OperationFinish = { status: OperationStatus, };
+export type OperationFinish = { status: OperationStatus, error: StageError | null, };
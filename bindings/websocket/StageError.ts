@@ -1,3 +1,17 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ErrorCode } from "./ErrorCode";
+import type { FailedFile } from "./FailedFile";
 
-export type StageError = Record<string, never>;
+export type StageError = { code: ErrorCode, message: string, 
+/**
+ * File, URL or other object the error is related to
+ */
+target: string | null, 
+/**
+ * Whether repeating the operation may succeed
+ */
+retryable: boolean, 
+/**
+ * Files which could not be downloaded after all retries
+ */
+failed_files: Array<FailedFile>, };
@@ -1,7 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConsoleMessage } from "./ConsoleMessage";
 import type { OperationMessage } from "./OperationMessage";
 import type { OptionUpdateMessage } from "./options/OptionUpdateMessage";
 import type { ScanMessage } from "./ScanMessage";
 import type { Task } from "./Task";
 
-export type WsMessage = { "type": "operation", "payload": OperationMessage } | { "type": "scan", "payload": ScanMessage } | { "type": "task", "payload": Task } | { "type": "option", "payload": OptionUpdateMessage };
+export type WsMessage = { "type": "operation", "payload": OperationMessage } | { "type": "scan", "payload": ScanMessage } | { "type": "task", "payload": Task } | { "type": "option", "payload": OptionUpdateMessage } | { "type": "console", "payload": ConsoleMessage };
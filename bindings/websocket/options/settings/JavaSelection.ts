@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Java used to run the instance
+ */
+export type JavaSelection = { "type": "auto" } | { "type": "system" } | { "type": "path", "value": string } | { "type": "managed", "value": string } | { "type": "detected", "value": bigint };
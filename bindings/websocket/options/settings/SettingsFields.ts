@@ -1,3 +1,9 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JavaSelection } from "./JavaSelection";
+import type { WindowSize } from "./WindowSize";
 
-export type SettingsFields = { dir: string | null, };
+export type SettingsFields = { dir: string | null, 
+/**
+ * Memory in MiB
+ */
+min_memory: number | null, max_memory: number | null, java: JavaSelection | null, jvm_args: Array<string> | null, game_args: Array<string> | null, env_vars: { [key in string]?: string } | null, window: WindowSize | null, };
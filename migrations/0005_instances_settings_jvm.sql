@@ -0,0 +1,17 @@
+-- Add migration script here
+ALTER TABLE instances_settings ADD COLUMN min_memory INTEGER;
+ALTER TABLE instances_settings ADD COLUMN max_memory INTEGER;
+
+-- Java executable override or a runtime managed by the launcher.
+-- Both are NULL for automatic selection: the runtime required by the version
+-- or an installation found by the system scan (`system_java_id`)
+ALTER TABLE instances_settings ADD COLUMN java_path TEXT;
+ALTER TABLE instances_settings ADD COLUMN java_runtime TEXT;
+
+-- JSON encoded arrays of strings and object of strings
+ALTER TABLE instances_settings ADD COLUMN jvm_args TEXT NOT NULL DEFAULT '[]';
+ALTER TABLE instances_settings ADD COLUMN game_args TEXT NOT NULL DEFAULT '[]';
+ALTER TABLE instances_settings ADD COLUMN env_vars TEXT NOT NULL DEFAULT '{}';
+
+ALTER TABLE instances_settings ADD COLUMN window_width INTEGER;
+ALTER TABLE instances_settings ADD COLUMN window_height INTEGER;
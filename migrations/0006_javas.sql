@@ -0,0 +1,22 @@
+-- Java runtimes installed by the launcher
+CREATE TABLE IF NOT EXISTS javas (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    component TEXT NOT NULL,
+    version TEXT NOT NULL,
+    arch TEXT NOT NULL,
+    path TEXT NOT NULL,
+    exec_path TEXT NOT NULL,
+    manifest_sha1 TEXT NOT NULL,
+    UNIQUE (component, arch)
+);
+
+-- Files, directories and links of the runtime.
+-- `hash` is sha1 of the raw file and NULL for directories and links
+CREATE TABLE IF NOT EXISTS java_files (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    java_id INTEGER NOT NULL REFERENCES javas(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    path_type TEXT NOT NULL,
+    hash TEXT,
+    UNIQUE (java_id, path)
+);
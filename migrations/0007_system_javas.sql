@@ -0,0 +1,10 @@
+-- Java installations found on the system, e.g. in JAVA_HOME or /usr/lib/jvm
+CREATE TABLE IF NOT EXISTS system_javas (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    vendor TEXT NOT NULL,
+    version TEXT NOT NULL,
+    major_version INTEGER NOT NULL,
+    arch TEXT NOT NULL,
+    path TEXT NOT NULL,
+    exec_path TEXT NOT NULL UNIQUE
+);
@@ -0,0 +1,3 @@
+-- Version of the mod loader, NULL for vanilla instances
+ALTER TABLE instances
+    ADD COLUMN loader_version TEXT;
@@ -0,0 +1,4 @@
+-- Java installation found by the system scan, reset to the automatic
+-- selection once the installation is gone
+ALTER TABLE instances_settings
+    ADD COLUMN system_java_id INTEGER REFERENCES system_javas(id) ON DELETE SET NULL;
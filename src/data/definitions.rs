@@ -1,11 +1,14 @@
 pub enum EnvVars {
-    HomeDirOverride
+    HomeDirOverride,
+    /// Path to the JSON file with the mirror table
+    MirrorsConfig,
 }
 
 impl EnvVars {
     pub fn as_str(&self) -> &'static str {
         match self {
-            EnvVars::HomeDirOverride => "HOME"
+            EnvVars::HomeDirOverride => "HOME",
+            EnvVars::MirrorsConfig => "SONATA_MIRRORS",
         }
     }
 }
@@ -3,12 +3,17 @@ use std::{collections::HashMap, path::PathBuf, process::exit, sync::Arc, usize};
 use async_std::sync::{Mutex, MutexGuard};
 use thiserror::Error;
 
-use crate::data::{config::Config, db::Database, instance::Instances, task::Tasks};
+use crate::{
+    data::{config::Config, db::Database, instance::Instances, process::Processes, task::Tasks},
+    java,
+    utils::download::cache::MetadataCache,
+};
 
 mod config;
 pub mod db;
 pub mod definitions;
 mod instance;
+pub mod process;
 mod task;
 
 pub type _GlobalAppDataGuard<'a> = MutexGuard<'a, GlobalAppData<'a>>;
@@ -17,6 +22,7 @@ pub type _GlobalAppDataGuard<'a> = MutexGuard<'a, GlobalAppData<'a>>;
 pub struct GlobalAppData<'a> {
     pub tasks: Tasks<'a>,
     pub instances: Instances,
+    pub processes: Processes,
 }
 
 #[derive(Debug, Clone)]
@@ -36,6 +42,9 @@ pub enum GlobalAppDataError {
     #[error("Task with id {0} not found")]
     TaskNotFound(usize),
 
+    #[error("Task with id {0} cannot be cancelled")]
+    TaskNotCancellable(usize),
+
     #[error("Failed to broadcast message: {0}")]
     BroadcastError(String),
 
@@ -49,11 +58,13 @@ impl<'a> GlobalDataState<'a> {
     pub async fn new() -> Self {
         let (task_tx, task_rx) = Self::create_task_broadcast();
         let (instances_tx, instances_rx) = Self::create_instance_broadcast();
+        let (processes_tx, processes_rx) = Self::create_process_broadcast();
 
         let data = GlobalAppData {
             tasks: Tasks {
                 tasks_map: Arc::new(Mutex::new(HashMap::new())),
                 notifier: task_tx,
+                cancel_tokens: Arc::new(Mutex::new(HashMap::new())),
                 _receiver: Arc::new(Mutex::new(task_rx)),
             },
             instances: Instances {
@@ -61,6 +72,11 @@ impl<'a> GlobalDataState<'a> {
                 notifier: instances_tx,
                 _reciever: Arc::new(Mutex::new(instances_rx)),
             },
+            processes: Processes {
+                processes_map: Arc::new(Mutex::new(HashMap::new())),
+                notifier: processes_tx,
+                _receiver: Arc::new(Mutex::new(processes_rx)),
+            },
         };
 
         let config = match Config::init().await {
@@ -82,10 +98,14 @@ impl<'a> GlobalDataState<'a> {
             }
         };
 
+        let launcher_root_path = config.take_launcher_root_path();
+        MetadataCache::global().set_dir(launcher_root_path.join("cache").join("metadata"));
+        java::metacache::import(&db, &launcher_root_path).await;
+
         Self {
             data,
             static_data: StaticData {
-                launcher_root_path: config.take_launcher_root_path(),
+                launcher_root_path,
                 db,
             },
         }
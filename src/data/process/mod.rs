@@ -0,0 +1,76 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_broadcast::{Receiver, Sender};
+use async_std::{process::Child, sync::Mutex};
+use chrono::{DateTime, Utc};
+
+use crate::data::task::TaskData;
+
+pub mod operations;
+
+pub type SharedProcess = Arc<Mutex<GameProcess>>;
+pub type ProcessesMap = HashMap<i64, ProcessSlot>;
+pub type ProcessesMapLocked = Arc<Mutex<ProcessesMap>>;
+
+#[derive(Debug, Clone)]
+pub struct Processes {
+    pub processes_map: ProcessesMapLocked,
+    pub notifier: Sender<serde_json::Value>,
+
+    // Add receiver to structure to let WebSocket connection stay alive
+    pub _receiver: Arc<Mutex<Receiver<serde_json::Value>>>,
+}
+
+/// Instances are reserved before their process is spawned,
+/// so the same instance can't be launched twice
+#[derive(Debug, Clone)]
+pub enum ProcessSlot {
+    Starting,
+    Running(SharedProcess),
+}
+
+impl ProcessSlot {
+    pub fn process(&self) -> Option<&SharedProcess> {
+        match self {
+            ProcessSlot::Starting => None,
+            ProcessSlot::Running(process) => Some(process),
+        }
+    }
+}
+
+/// Game process spawned by the supervisor, keyed by instance id
+#[derive(Debug)]
+pub struct GameProcess {
+    pub instance_id: i64,
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    pub child: Child,
+
+    /// Internal task that reflects the process state
+    pub task: TaskData<'static>,
+
+    /// Set once a stop is requested, so the exit is reported as cancelled
+    pub stopping: bool,
+
+    /// Closed by the supervisor once the process exit is handled
+    pub exited: async_channel::Receiver<()>,
+}
+
+impl GameProcess {
+    pub fn new_shared(
+        instance_id: i64,
+        child: Child,
+        task: TaskData<'static>,
+        exited: async_channel::Receiver<()>,
+    ) -> SharedProcess {
+        Arc::new(Mutex::new(GameProcess {
+            instance_id,
+            pid: child.id(),
+            started_at: Utc::now(),
+            child,
+            task,
+            stopping: false,
+            exited,
+        }))
+    }
+}
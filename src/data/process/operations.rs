@@ -0,0 +1,109 @@
+use async_broadcast::broadcast;
+use serde_json::json;
+
+use crate::{
+    data::{GlobalAppDataError, GlobalDataState, GlobalDataStateResult},
+    websocket::messages::{
+        console::{ConsoleEvent, ConsoleMessage},
+        WsMessage,
+    },
+};
+
+use super::*;
+
+const CONSOLE_BROADCAST_CAPACITY: usize = 1024;
+
+impl Processes {
+    pub async fn notify(&self, event: ConsoleEvent) -> GlobalDataStateResult<()> {
+        let msg: WsMessage = ConsoleMessage::from(event).into();
+
+        match self.notifier.broadcast(json!(msg)).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(GlobalAppDataError::BroadcastError(e.to_string())),
+        }
+    }
+
+    pub async fn get(&self, instance_id: i64) -> Option<SharedProcess> {
+        let data = self.processes_map.lock().await;
+        data.get(&instance_id).and_then(ProcessSlot::process).cloned()
+    }
+
+    pub async fn remove(&self, instance_id: i64) -> Option<SharedProcess> {
+        let mut data = self.processes_map.lock().await;
+        data.remove(&instance_id).and_then(|slot| slot.process().cloned())
+    }
+
+    /// Reserves the slot of the instance before its process is spawned.
+    /// Returns `false` if the instance is already running or being started
+    pub async fn reserve(&self, instance_id: i64) -> bool {
+        let mut data = self.processes_map.lock().await;
+        if data.contains_key(&instance_id) {
+            return false;
+        }
+
+        data.insert(instance_id, ProcessSlot::Starting);
+        true
+    }
+
+    /// Drops the reservation if the process wasn't spawned
+    pub async fn release(&self, instance_id: i64) {
+        let mut data = self.processes_map.lock().await;
+        if let Some(ProcessSlot::Starting) = data.get(&instance_id) {
+            data.remove(&instance_id);
+        }
+    }
+}
+
+impl<'a> GlobalDataState<'a> {
+    pub fn create_process_broadcast() -> (Sender<serde_json::Value>, Receiver<serde_json::Value>) {
+        let (mut tx, rx) = broadcast(CONSOLE_BROADCAST_CAPACITY);
+        tx.set_overflow(true);
+
+        (tx, rx)
+    }
+
+    pub fn create_process_reciever(&self) -> Receiver<serde_json::Value> {
+        self.data.processes.notifier.new_receiver()
+    }
+
+    pub async fn add_process(&self, process: SharedProcess) -> GlobalDataStateResult<()> {
+        let processes = &self.data.processes;
+        let (instance_id, pid) = {
+            let process = process.lock().await;
+            (process.instance_id, process.pid)
+        };
+
+        processes
+            .processes_map
+            .lock()
+            .await
+            .insert(instance_id, ProcessSlot::Running(process));
+
+        processes
+            .notify(ConsoleEvent::Started { instance_id, pid })
+            .await
+    }
+
+    pub async fn is_instance_running(&self, instance_id: i64) -> bool {
+        let data = self.data.processes.processes_map.lock().await;
+        data.contains_key(&instance_id)
+    }
+
+    pub async fn get_all_processes_json(&self) -> Vec<serde_json::Value> {
+        let data = self.data.processes.processes_map.lock().await;
+        let mut processes = Vec::new();
+
+        for process in data.values().filter_map(ProcessSlot::process) {
+            let process = process.lock().await;
+            let msg: WsMessage = ConsoleMessage::from(ConsoleEvent::Started {
+                instance_id: process.instance_id,
+                pid: process.pid,
+            })
+            .into();
+
+            processes.push(json!(msg));
+        }
+
+        processes
+    }
+}
@@ -3,9 +3,12 @@ use std::{collections::HashMap, sync::{Arc, Weak}};
 use async_broadcast::{Receiver, Sender};
 use async_std::sync::Mutex;
 
-use crate::websocket::messages::{
-    operation::stage::OperationStage,
-    task::{Task, TaskProgress, TaskStatus},
+use crate::{
+    utils::cancel::CancellationToken,
+    websocket::messages::{
+        operation::stage::OperationStage,
+        task::{Task, TaskProgress, TaskStatus},
+    },
 };
 
 pub mod operations;
@@ -13,6 +16,7 @@ pub mod operations;
 pub type SharedTask<'a> = Arc<Mutex<Task<'a>>>;
 pub type TasksMap<'a> = HashMap<usize, Weak<Mutex<Task<'a>>>>;
 pub type TasksMapLocked<'a> = Arc<Mutex<TasksMap<'a>>>;
+pub type CancelTokensLocked = Arc<Mutex<HashMap<usize, CancellationToken>>>;
 
 #[derive(Debug)]
 pub struct TaskData<'a> {
@@ -25,6 +29,9 @@ pub struct Tasks<'a> {
     pub tasks_map: TasksMapLocked<'a>,
     pub notifier: Sender<serde_json::Value>,
 
+    // Tokens of the tasks which can be cancelled, keyed by task id
+    pub cancel_tokens: CancelTokensLocked,
+
     // Add receiver to structure to let websocket connection stay alive
     pub _receiver: Arc<Mutex<Receiver<serde_json::Value>>>,
 }
@@ -72,6 +72,40 @@ impl<'a> GlobalDataState<'a> {
         }
     }
 
+    /// Registers a cancellation token for the task.
+    /// Operation should keep the returned token and check it in its loops
+    pub async fn create_cancel_token(&self, task_id: usize) -> CancellationToken {
+        let token = CancellationToken::new();
+        let mut tokens = self.data.tasks.cancel_tokens.lock().await;
+        tokens.insert(task_id, token.clone());
+
+        token
+    }
+
+    pub async fn remove_cancel_token(&self, task_id: usize) {
+        let mut tokens = self.data.tasks.cancel_tokens.lock().await;
+        tokens.remove(&task_id);
+    }
+
+    /// Requests cancellation of the task.
+    /// Task stays in `CancelledAwaiting` until the operation stops itself
+    pub async fn cancel_task(&self, task_id: usize) -> GlobalDataStateResult<()> {
+        // Operations remove their token before storing the final status,
+        // so holding the lock keeps that status from being overwritten
+        let tokens = self.data.tasks.cancel_tokens.lock().await;
+        match tokens.get(&task_id) {
+            Some(token) => token.cancel(),
+            None => return Err(GlobalAppDataError::TaskNotCancellable(task_id)),
+        }
+
+        self.update_task(task_id, |t| {
+            if matches!(t.status, TaskStatus::Pending | TaskStatus::Running) {
+                t.status = TaskStatus::CancelledAwaiting;
+            }
+        })
+        .await
+    }
+
     pub async fn get_all_tasks_json(&self) -> Vec<serde_json::Value> {
         let tasks = &self.data.tasks;
         let data = tasks.tasks_map.lock().await;
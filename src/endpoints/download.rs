@@ -0,0 +1,119 @@
+use http_types::mime::JSON;
+use serde_json::json;
+
+use crate::utils::download::{
+    cache::{CacheSettings, MetadataCache},
+    mirror::{MirrorConfig, Mirrors},
+    retry::RetryPolicy,
+    scheduler::{DownloadLimits, DownloadScheduler},
+};
+use crate::EndpointRequest;
+
+pub async fn get_download_limits<'a>(_req: EndpointRequest<'a>) -> tide::Result {
+    Ok(tide::Response::builder(200)
+        .body(json!(DownloadScheduler::global().limits()))
+        .content_type(JSON)
+        .build())
+}
+
+/// Change limits of all downloads of the launcher
+pub async fn set_download_limits<'a>(mut req: EndpointRequest<'a>) -> tide::Result {
+    let limits: DownloadLimits = req.body_json().await?;
+    if limits.max_concurrent == 0 || limits.max_per_host == 0 {
+        return Ok(tide::Response::builder(400)
+            .body("Download limits must be greater than 0")
+            .build());
+    }
+
+    DownloadScheduler::global().set_limits(limits);
+
+    Ok(tide::Response::builder(200)
+        .body(json!(limits))
+        .content_type(JSON)
+        .build())
+}
+
+pub async fn get_mirrors<'a>(_req: EndpointRequest<'a>) -> tide::Result {
+    Ok(tide::Response::builder(200)
+        .body(json!(Mirrors::global().config()))
+        .content_type(JSON)
+        .build())
+}
+
+/// Replace the mirror table used by all outbound requests
+pub async fn set_mirrors<'a>(mut req: EndpointRequest<'a>) -> tide::Result {
+    let config: MirrorConfig = req.body_json().await?;
+    if config.rules.iter().any(|rule| rule.from.is_empty()) {
+        return Ok(tide::Response::builder(400)
+            .body("Mirror rule must have a URL prefix to replace")
+            .build());
+    }
+
+    Mirrors::global().set_config(config.clone());
+
+    Ok(tide::Response::builder(200)
+        .body(json!(config))
+        .content_type(JSON)
+        .build())
+}
+
+pub async fn get_retry_policy<'a>(_req: EndpointRequest<'a>) -> tide::Result {
+    Ok(tide::Response::builder(200)
+        .body(json!(RetryPolicy::global()))
+        .content_type(JSON)
+        .build())
+}
+
+/// Change how failed downloads are repeated
+pub async fn set_retry_policy<'a>(mut req: EndpointRequest<'a>) -> tide::Result {
+    let policy: RetryPolicy = req.body_json().await?;
+    if policy.attempts == 0 {
+        return Ok(tide::Response::builder(400)
+            .body("Retry policy must have at least 1 attempt")
+            .build());
+    }
+    if !(0.0..=1.0).contains(&policy.jitter) {
+        return Ok(tide::Response::builder(400)
+            .body("Retry jitter must be from 0.0 to 1.0")
+            .build());
+    }
+
+    RetryPolicy::set_global(policy.clone());
+
+    Ok(tide::Response::builder(200)
+        .body(json!(policy))
+        .content_type(JSON)
+        .build())
+}
+
+pub async fn get_cache_settings<'a>(_req: EndpointRequest<'a>) -> tide::Result {
+    Ok(tide::Response::builder(200)
+        .body(json!(MetadataCache::global().settings()))
+        .content_type(JSON)
+        .build())
+}
+
+/// Change TTLs of the cached metadata
+pub async fn set_cache_settings<'a>(mut req: EndpointRequest<'a>) -> tide::Result {
+    let settings: CacheSettings = req.body_json().await?;
+    MetadataCache::global().set_settings(settings.clone());
+
+    Ok(tide::Response::builder(200)
+        .body(json!(settings))
+        .content_type(JSON)
+        .build())
+}
+
+/// Remove cached metadata, so it is downloaded again on the next request
+pub async fn clear_cache<'a>(_req: EndpointRequest<'a>) -> tide::Result {
+    match MetadataCache::global().clear().await {
+        Ok(_) => Ok(tide::Response::builder(200)
+            .body(json!({ "message": "cache cleared" }))
+            .content_type(JSON)
+            .build()),
+        Err(e) => Ok(tide::Response::builder(500)
+            .body(json!({ "message": format!("Failed to clear cache: {}", e) }))
+            .content_type(JSON)
+            .build()),
+    }
+}
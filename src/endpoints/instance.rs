@@ -1,20 +1,27 @@
+use std::time::Duration;
+
 use async_std::stream::StreamExt;
 use chrono::Utc;
 use http_types::mime::PLAIN;
+use serde::Deserialize;
 use serde_json::json;
 use tide::StatusCode;
 use tide_websockets::Message;
 use tide_websockets::WebSocketConnection;
 
+use crate::instance::launch::supervisor::SupervisorError;
+use crate::instance::launch::supervisor::DEFAULT_STOP_TIMEOUT;
 use crate::instance::options::pages::overview::OverviewFields;
 use crate::instance::options::pages::settings::SettingsFields;
 use crate::instance::options::pages::Page;
 use crate::instance::options::ChangeRequestBuilder;
 use crate::instance::InitData;
 use crate::instance::Instance;
+use crate::instance::InstanceError;
 use crate::instance::RunData;
 use crate::websocket::messages::option::InstanceFields;
 use crate::websocket::messages::option::OptionUpdateMessage;
+use crate::websocket::messages::operation::stage::StageError;
 use crate::websocket::messages::BaseMessage;
 use crate::websocket::messages::WsMessage;
 use crate::websocket::messages::WsMessageType;
@@ -42,7 +49,8 @@ pub async fn init_instance_ws<'a>(
                 println!("{e}");
                 response = json!({
                     "result": format!("Failed"),
-                    "error": format!("Failed to create instance, {}", e)
+                    "error": format!("Failed to create instance, {}", e),
+                    "code": StageError::from(&e).code
                 });
             }
         }
@@ -53,9 +61,44 @@ pub async fn init_instance_ws<'a>(
     Ok(())
 }
 
-pub async fn run_instance_ws<'a>(
+pub async fn verify_instance_ws<'a>(
     req: EndpointRequest<'a>,
     mut ws: WebSocketConnection,
+) -> tide::Result<()> {
+    while let Some(Ok(Message::Text(input))) = ws.next().await {
+        let data: InitData = serde_json::from_str(&input).map_err(|e| {
+            println!("Failed to parse JSON");
+            tide::Error::from_str(400, format!("Failed to parse recieved JSON: {}", e))
+        })?;
+
+        let response = match Instance::verify(data, &req, &ws).await {
+            Ok(_) => json!({
+                "message": "instance verified"
+            }),
+            Err(e) => {
+                println!("{e}");
+                json!({
+                    "result": "Failed",
+                    "error": format!("Failed to verify instance, {}", e),
+                    "code": StageError::from(&e).code
+                })
+            }
+        };
+
+        ws.send_string(format!("{response}")).await?;
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct StopQuery {
+    timeout_secs: Option<u64>,
+}
+
+pub async fn run_instance_ws(
+    req: EndpointRequest<'static>,
+    mut ws: WebSocketConnection,
 ) -> tide::Result<()> {
     while let Some(Ok(Message::Text(input))) = ws.next().await {
         let run_data: RunData = serde_json::from_str(&input).map_err(|e| {
@@ -65,7 +108,7 @@ pub async fn run_instance_ws<'a>(
 
         let response: serde_json::Value;
         match Instance::run(run_data, &req, &ws).await {
-            Ok(result) => response = json!(result),
+            Ok(pid) => response = json!({ "pid": pid }),
 
             Err(e) => {
                 println!("{e}");
@@ -82,6 +125,41 @@ pub async fn run_instance_ws<'a>(
     Ok(())
 }
 
+pub async fn stop_instance<'a>(req: EndpointRequest<'a>) -> tide::Result {
+    let id_param = req
+        .param("id")
+        .map_err(|_| tide::Error::from_str(StatusCode::BadRequest, "Missing ID"))?;
+    let id = match id_param.parse::<i64>() {
+        Ok(id) => id,
+        Err(e) => {
+            return Ok(tide::Response::builder(400)
+                .body(e.to_string())
+                .content_type(tide::http::mime::PLAIN)
+                .build())
+        }
+    };
+
+    let StopQuery { timeout_secs } = req.query()?;
+    let grace_period = timeout_secs
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_STOP_TIMEOUT);
+
+    match Instance::stop(&req, id, grace_period).await {
+        Ok(_) => Ok(tide::Response::builder(200).build()),
+        Err(e) => {
+            let code = match e {
+                InstanceError::Process(SupervisorError::NotRunning(_)) => 404,
+                _ => 500,
+            };
+
+            Ok(tide::Response::builder(code)
+                .body(e.to_string())
+                .content_type(PLAIN)
+                .build())
+        }
+    }
+}
+
 pub async fn instance_dispather<'a>(req: EndpointRequest<'a>, ws: WebSocketConnection) -> tide::Result<()> {
     let all_instances = req.state().get_all_instances_json().await;
 
@@ -110,6 +188,35 @@ pub async fn instance_dispather<'a>(req: EndpointRequest<'a>, ws: WebSocketConne
     Ok(())
 }
 
+pub async fn instance_console_ws<'a>(req: EndpointRequest<'a>, ws: WebSocketConnection) -> tide::Result<()> {
+    // Report already running instances first
+    let running = req.state().get_all_processes_json().await;
+
+    for process_json in running {
+        if ws.send(Message::text(process_json.to_string())).await.is_err() {
+            println!("Failed to send running instances");
+        }
+    }
+
+    let mut rx = req.state().create_process_reciever();
+
+    loop {
+        match rx.recv().await {
+            Ok(notif) => {
+                if ws.send(Message::text(notif.to_string())).await.is_err() {
+                    break;
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to receive notification: {:?}", e);
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 pub async fn instance_options_dispatcher<'a>(req: EndpointRequest<'a>) -> tide::Result {
     let id_param = req
         .param("id")
@@ -186,7 +293,8 @@ pub async fn instance_options_sync(mut ws: WebSocketConnection) -> tide::Result<
                 timestamp: Utc::now(),
             },
             option: InstanceFields::Settings(SettingsFields {
-                dir: Some("/Users/quartix/.sonata/instances/123".into())
+                dir: Some("/Users/quartix/.sonata/instances/123".into()),
+                ..Default::default()
             })
         });
 
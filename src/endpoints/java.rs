@@ -1,10 +1,25 @@
-use async_std::stream::StreamExt;
-use home::home_dir;
+use async_std::{stream::StreamExt, task};
+use http_types::mime::{JSON, PLAIN};
 use serde::Deserialize;
+use serde_json::json;
+use tide::StatusCode;
 use tide_websockets::WebSocketConnection;
 use tide_websockets::Message;
 
-use crate::java::Java;
+use crate::data::db::DBError;
+use crate::instance::websocket::OperationWsMessage;
+use crate::websocket::messages::operation::{
+    error::ErrorCode,
+    event::OperationStatus,
+    stage::{OperationStage, StageError},
+};
+use crate::java::{
+    runtime::JavaRuntime,
+    system::{SystemJava, SystemJavaError},
+    Java, JavaRequirement,
+};
+use crate::websocket::messages::task::{Task, TaskProgress, TaskStatus};
+use crate::EndpointRequest;
 
 
 #[derive(Debug, Deserialize)]
@@ -12,7 +27,18 @@ struct DownloadRequest {
     java_ver: String,
 }
 
-pub async fn download_java_ws(mut ws: WebSocketConnection) -> tide::Result<()> {
+#[derive(Debug, Deserialize)]
+struct VerifyRequest {
+    request_id: String,
+    java_id: i64,
+}
+
+pub async fn download_java_ws<'a>(
+    req: EndpointRequest<'a>,
+    mut ws: WebSocketConnection,
+) -> tide::Result<()> {
+    let state = req.state();
+
     while let Some(Ok(Message::Text(input))) = ws.next().await {
         let download_request: DownloadRequest = serde_json::from_str(&input).map_err(|e| {
             tide::Error::from_str(400, format!("Failed to parse recieved JSON: {}", e))
@@ -20,22 +46,339 @@ pub async fn download_java_ws(mut ws: WebSocketConnection) -> tide::Result<()> {
 
         let DownloadRequest { java_ver } = download_request;
 
-        match home_dir() {
-            Some(path) => {
-                let java_path = format!("{}/.sonata/java", path.display());
-                let metacache_path = format!("{}/.sonata/metacache.json", path.display());
-                let java_properties = Java::new(
-                    "21".to_string(),
-                    "java-runtime-delta".to_string(),
-                    java_path,
-                );
-                Java::init(java_properties, metacache_path).await.unwrap();
+        let major = match java_ver.parse::<u64>() {
+            Ok(major) => major,
+            Err(e) => {
+                let response = json!({
+                    "result": "Failed",
+                    "error": format!("Invalid java version {java_ver}: {e}"),
+                });
+                ws.send_string(format!("{response}")).await?;
+                continue;
             }
-            None => (),
         };
 
+        let requirement = JavaRequirement::from_major_version(major);
+        let launcher_root = &state.static_data.launcher_root_path;
+        let java_path = launcher_root
+            .join("java")
+            .join(&requirement.component)
+            .display()
+            .to_string();
+        let java_properties = Java::new(requirement.major_version, requirement.component, java_path);
+
+        let task_handle = state
+            .add_task(Task::new_shared(
+                "Install Java",
+                TaskStatus::Running,
+                None,
+                TaskProgress::Indeterminable,
+                None,
+            ))
+            .await?;
+        let cancel = state.create_cancel_token(task_handle.id).await;
+
+        let result = Java::init(java_properties, &state.static_data.db, &cancel).await;
+        state.remove_cancel_token(task_handle.id).await;
+
+        let (status, response) = match result {
+            Ok(_) => (TaskStatus::Completed, json!({
+                "message": "java installed",
+                "task_id": task_handle.id
+            })),
+            Err(e) => {
+                let status = if cancel.is_cancelled() {
+                    TaskStatus::Cancelled
+                } else {
+                    TaskStatus::Failed
+                };
+
+                (status, json!({
+                    "result": "Failed",
+                    "error": format!("Failed to install java, {}", e),
+                    "task_id": task_handle.id
+                }))
+            }
+        };
+
+        state.update_task(task_handle.id, |t| t.status = status).await?;
+        ws.send_string(format!("{response}")).await?;
+
         println!("Recieved java version: {}", java_ver);
     }
 
     Ok(())
 }
+
+fn db_error_response(e: DBError) -> tide::Response {
+    let code = match e {
+        DBError::NotFound(_) => StatusCode::NotFound,
+        _ => StatusCode::InternalServerError,
+    };
+
+    tide::Response::builder(code)
+        .body(e.to_string())
+        .content_type(PLAIN)
+        .build()
+}
+
+pub async fn list_javas<'a>(req: EndpointRequest<'a>) -> tide::Result {
+    match JavaRuntime::list(&req.state().static_data.db).await {
+        Ok(javas) => Ok(tide::Response::builder(200)
+            .body(json!(javas))
+            .content_type(JSON)
+            .build()),
+        Err(e) => Ok(db_error_response(e)),
+    }
+}
+
+pub async fn get_java<'a>(req: EndpointRequest<'a>) -> tide::Result {
+    let id_param = req
+        .param("id")
+        .map_err(|_| tide::Error::from_str(StatusCode::BadRequest, "Missing ID"))?;
+    let id = match id_param.parse::<i64>() {
+        Ok(id) => id,
+        Err(e) => {
+            return Ok(tide::Response::builder(400)
+                .body(e.to_string())
+                .content_type(PLAIN)
+                .build())
+        }
+    };
+
+    match JavaRuntime::get(&req.state().static_data.db, id).await {
+        Ok(java) => Ok(tide::Response::builder(200)
+            .body(json!(java))
+            .content_type(JSON)
+            .build()),
+        Err(e) => Ok(db_error_response(e)),
+    }
+}
+
+/// Remove the runtime from DB and its files from disk
+pub async fn delete_java<'a>(req: EndpointRequest<'a>) -> tide::Result {
+    let id_param = req
+        .param("id")
+        .map_err(|_| tide::Error::from_str(StatusCode::BadRequest, "Missing ID"))?;
+    let id = match id_param.parse::<i64>() {
+        Ok(id) => id,
+        Err(e) => {
+            return Ok(tide::Response::builder(400)
+                .body(e.to_string())
+                .content_type(PLAIN)
+                .build())
+        }
+    };
+
+    let java = match JavaRuntime::delete(&req.state().static_data.db, id).await {
+        Ok(java) => java,
+        Err(e) => return Ok(db_error_response(e)),
+    };
+
+    // Runtime directory could be already removed by the user
+    let removed = match async_std::fs::remove_dir_all(&java.path).await {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    };
+    if let Err(e) = removed {
+        eprintln!("Failed to remove java runtime {}: {}", java.path, e);
+        return Ok(tide::Response::builder(500)
+            .body(format!("Runtime is unregistered, but its files were not removed: {e}"))
+            .content_type(PLAIN)
+            .build());
+    }
+
+    Ok(tide::Response::builder(200).build())
+}
+
+pub async fn list_system_javas<'a>(req: EndpointRequest<'a>) -> tide::Result {
+    match SystemJava::list(&req.state().static_data.db).await {
+        Ok(javas) => Ok(tide::Response::builder(200)
+            .body(json!(javas))
+            .content_type(JSON)
+            .build()),
+        Err(e) => Ok(db_error_response(e)),
+    }
+}
+
+/// Start the scan for system Java installations as a task.
+/// Found installations are listed by `list_system_javas` once it is completed
+pub async fn scan_system_javas(req: &EndpointRequest<'static>) -> serde_json::Value {
+    let state = req.state();
+    let task_handle = match state
+        .add_task(Task::new_shared(
+            "Scan Java installations",
+            TaskStatus::Running,
+            None,
+            TaskProgress::Determinable {
+                current: None,
+                total: None,
+            },
+            None,
+        ))
+        .await
+    {
+        Ok(handle) => handle,
+        Err(e) => {
+            return json!({
+                "result": "Failed",
+                "error": format!("Failed to scan java installations, {}", e)
+            })
+        }
+    };
+    let task_id = task_handle.id;
+    let cancel = state.create_cancel_token(task_id).await;
+
+    // The scan runs in the background, so the connection is able to cancel it
+    let state = state.clone();
+    task::spawn(async move {
+        let result = SystemJava::scan(&state, task_id, &cancel).await;
+        state.remove_cancel_token(task_id).await;
+
+        let status = match result {
+            Ok(javas) => {
+                println!("Found {} java installations", javas.len());
+                TaskStatus::Completed
+            }
+            Err(SystemJavaError::Cancelled) => TaskStatus::Cancelled,
+            Err(e) => {
+                eprintln!("Failed to scan java installations: {}", e);
+                TaskStatus::Failed
+            }
+        };
+
+        if let Err(e) = state.update_task(task_id, |t| t.status = status).await {
+            eprintln!("Failed to update java scan task: {}", e);
+        }
+    });
+
+    json!({
+        "message": "java installations scan started",
+        "task_id": task_id
+    })
+}
+
+pub async fn verify_java_ws<'a>(
+    req: EndpointRequest<'a>,
+    mut ws: WebSocketConnection,
+) -> tide::Result<()> {
+    while let Some(Ok(Message::Text(input))) = ws.next().await {
+        let VerifyRequest { request_id, java_id } = serde_json::from_str(&input).map_err(|e| {
+            tide::Error::from_str(400, format!("Failed to parse recieved JSON: {}", e))
+        })?;
+
+        let response = verify_java(&req, &ws, &request_id, java_id).await;
+        ws.send_string(format!("{response}")).await?;
+    }
+
+    Ok(())
+}
+
+/// Verify and repair the runtime as a task
+async fn verify_java<'a>(
+    req: &EndpointRequest<'a>,
+    ws: &WebSocketConnection,
+    request_id: &str,
+    java_id: i64,
+) -> serde_json::Value {
+    let state = req.state();
+    let db = &state.static_data.db;
+
+    let runtime = match JavaRuntime::get(db, java_id).await {
+        Ok(details) => details.runtime,
+        Err(e) => {
+            return json!({
+                "result": "Failed",
+                "error": format!("Failed to verify java, {}", e)
+            })
+        }
+    };
+
+    let task_handle = match state
+        .add_task(Task::new_shared(
+            "Verify Java",
+            TaskStatus::Running,
+            Some(OperationStage::VerifyFiles),
+            TaskProgress::Indeterminable,
+            None,
+        ))
+        .await
+    {
+        Ok(handle) => handle,
+        Err(e) => {
+            return json!({
+                "result": "Failed",
+                "error": format!("Failed to verify java, {}", e)
+            })
+        }
+    };
+    let cancel = state.create_cancel_token(task_handle.id).await;
+
+    let ws_status = OperationWsMessage::create_task(
+        ws,
+        request_id,
+        task_handle.id,
+        vec![OperationStage::VerifyFiles],
+    )
+    .await;
+    let result = Java::from_runtime(&runtime)
+        .verify(db, &runtime.exec_path, ws_status, &cancel)
+        .await;
+    state.remove_cancel_token(task_handle.id).await;
+
+    let (status, operation_status, error, response) = match result {
+        Ok(report) if report.failed.is_empty() => (
+            TaskStatus::Completed,
+            OperationStatus::Completed,
+            None,
+            json!({
+                "message": "java verified",
+                "task_id": task_handle.id,
+                "report": report
+            }),
+        ),
+        Ok(report) => (
+            TaskStatus::Failed,
+            OperationStatus::Failed,
+            Some(
+                StageError::new(ErrorCode::Network, "Failed to repair some files of java")
+                    .with_target(runtime.path.clone())
+                    .retryable(true),
+            ),
+            json!({
+                "result": "Failed",
+                "error": "Failed to repair some files of java",
+                "task_id": task_handle.id,
+                "report": report
+            }),
+        ),
+        Err(e) => {
+            let (status, operation_status, code) = if cancel.is_cancelled() {
+                (TaskStatus::Cancelled, OperationStatus::Cancelled, ErrorCode::Cancelled)
+            } else {
+                (TaskStatus::Failed, OperationStatus::Failed, ErrorCode::Internal)
+            };
+            let error = StageError::new(code, e.clone()).with_target(runtime.path.clone());
+
+            (status, operation_status, Some(error), json!({
+                "result": "Failed",
+                "error": format!("Failed to verify java, {}", e),
+                "task_id": task_handle.id
+            }))
+        }
+    };
+
+    if let Err(e) = state
+        .update_task(task_handle.id, |t| {
+            t.stage = None;
+            t.status = status;
+        })
+        .await
+    {
+        eprintln!("Failed to update java verification task: {}", e);
+    }
+    OperationWsMessage::finish_operation(ws, request_id, task_handle.id, operation_status, error)
+        .await;
+
+    response
+}
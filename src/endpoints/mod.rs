@@ -6,9 +6,11 @@ use tide_websockets::Message;
 use crate::root::LauncherRoot;
 use crate::EndpointRequest;
 
+pub mod download;
 pub mod java;
 pub mod versions;
 pub mod instance;
+pub mod task;
 
 
 pub async fn debug_ws(mut stream: WebSocketConnection) -> tide::Result<()> {
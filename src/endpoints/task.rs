@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use async_std::stream::StreamExt;
+use http_types::mime::PLAIN;
+use serde_json::json;
+use tide::StatusCode;
+use tide_websockets::Message;
+use tide_websockets::WebSocketConnection;
+
+use crate::endpoints::java::scan_system_javas;
+use crate::instance::launch::supervisor::DEFAULT_STOP_TIMEOUT;
+use crate::instance::Instance;
+use crate::websocket::messages::operation::RequestedTask;
+use crate::data::GlobalAppDataError;
+use crate::EndpointRequest;
+
+pub async fn cancel_task<'a>(req: EndpointRequest<'a>) -> tide::Result {
+    let id_param = req
+        .param("id")
+        .map_err(|_| tide::Error::from_str(StatusCode::BadRequest, "Missing ID"))?;
+    let id = match id_param.parse::<usize>() {
+        Ok(id) => id,
+        Err(e) => {
+            return Ok(tide::Response::builder(400)
+                .body(e.to_string())
+                .content_type(PLAIN)
+                .build())
+        }
+    };
+
+    match req.state().cancel_task(id).await {
+        Ok(_) => Ok(tide::Response::builder(202).build()),
+        Err(e) => {
+            let code = match e {
+                GlobalAppDataError::TaskNotCancellable(_)
+                | GlobalAppDataError::TaskNotFound(_) => 404,
+                _ => 500,
+            };
+
+            Ok(tide::Response::builder(code)
+                .body(e.to_string())
+                .content_type(PLAIN)
+                .build())
+        }
+    }
+}
+
+pub async fn requested_task_ws(
+    req: EndpointRequest<'static>,
+    mut ws: WebSocketConnection,
+) -> tide::Result<()> {
+    while let Some(Ok(Message::Text(input))) = ws.next().await {
+        let requested_task: RequestedTask = serde_json::from_str(&input).map_err(|e| {
+            tide::Error::from_str(400, format!("Failed to parse recieved JSON: {}", e))
+        })?;
+
+        let response = match requested_task {
+            RequestedTask::StopInstance {
+                instance_id,
+                timeout_secs,
+            } => {
+                let grace_period = timeout_secs
+                    .map(Duration::from_secs)
+                    .unwrap_or(DEFAULT_STOP_TIMEOUT);
+
+                match Instance::stop(&req, instance_id, grace_period).await {
+                    Ok(_) => json!({
+                        "message": "instance stopped"
+                    }),
+                    Err(e) => json!({
+                        "result": "Failed",
+                        "error": format!("Failed to stop instance, {}", e)
+                    }),
+                }
+            }
+            RequestedTask::CancelTask { task_id } => match req.state().cancel_task(task_id).await {
+                Ok(_) => json!({
+                    "message": "task cancellation requested"
+                }),
+                Err(e) => json!({
+                    "result": "Failed",
+                    "error": format!("Failed to cancel task, {}", e)
+                }),
+            },
+            RequestedTask::ScanJavas => scan_system_javas(&req).await,
+            _ => json!({
+                "result": "Failed",
+                "error": "Requested task is not supported yet"
+            }),
+        };
+
+        ws.send_string(format!("{response}")).await?;
+    }
+
+    Ok(())
+}
@@ -7,7 +7,8 @@ use tide_websockets::Message;
 use tide_websockets::WebSocketConnection;
 
 use crate::manifest::get_version_manifest;
-use crate::utils::unify::UnifiedVersionsData;
+use crate::utils::download::download_in_json;
+use crate::utils::unify::{catalog::CatalogQuery, MetaProviders, UnifiedVersionsData};
 use crate::EndpointRequest;
 
 #[derive(Debug, Deserialize)]
@@ -27,12 +28,28 @@ struct ManifestRequest {
     manifest_type: ManifestTypes,
 }
 
-pub async fn get_versions_unified<'a>(mut _req: EndpointRequest<'a>) -> tide::Result {
-    let unified_versions =
-        match UnifiedVersionsData::new(crate::utils::unify::MetaProviders::Prism).await {
-            Ok(manifest) => manifest,
-            Err(e) => return Ok(tide::Response::builder(e).build()),
-        };
+#[derive(Deserialize)]
+struct UnifiedVersionsQuery {
+    #[serde(default)]
+    provider: MetaProviders,
+}
+
+/// Versions of the `provider` query parameter (`mojang` or `prism`, Prism by default)
+pub async fn get_versions_unified<'a>(req: EndpointRequest<'a>) -> tide::Result {
+    let UnifiedVersionsQuery { provider } = match req.query() {
+        Ok(query) => query,
+        Err(e) => {
+            return Ok(tide::Response::builder(400)
+                .body(format!("Unknown provider: {}", e))
+                .content_type(tide::http::mime::PLAIN)
+                .build())
+        }
+    };
+
+    let unified_versions = match UnifiedVersionsData::new(provider).await {
+        Ok(manifest) => manifest,
+        Err(e) => return Ok(tide::Response::builder(e).build()),
+    };
 
     match unified_versions.build() {
         Ok(data) => Ok(tide::Response::builder(200)
@@ -46,32 +63,61 @@ pub async fn get_versions_unified<'a>(mut _req: EndpointRequest<'a>) -> tide::Re
     }
 }
 
+/// Filtered, sorted and paginated versions, see `CatalogQuery`
+pub async fn get_versions_catalog<'a>(req: EndpointRequest<'a>) -> tide::Result {
+    let query: CatalogQuery = match req.query() {
+        Ok(query) => query,
+        Err(e) => {
+            return Ok(tide::Response::builder(400)
+                .body(json!({ "message": format!("Invalid query: {}", e) }))
+                .content_type(tide::http::mime::JSON)
+                .build())
+        }
+    };
+
+    let unified_versions = match UnifiedVersionsData::new(query.provider).await {
+        Ok(manifest) => manifest,
+        Err(e) => return Ok(tide::Response::builder(e).build()),
+    };
+
+    let versions = match unified_versions.catalog() {
+        Ok(versions) => versions,
+        Err(e) => {
+            return Ok(tide::Response::builder(422)
+                .body(json!({ "message": e }))
+                .content_type(tide::http::mime::JSON)
+                .build())
+        }
+    };
+
+    match query.apply(versions) {
+        Ok(page) => Ok(tide::Response::builder(200)
+            .body(json!(page))
+            .content_type(tide::http::mime::JSON)
+            .build()),
+        Err(e) => Ok(tide::Response::builder(400)
+            .body(json!({ "message": e }))
+            .content_type(tide::http::mime::JSON)
+            .build()),
+    }
+}
+
 pub async fn get_versions<'a>(mut req: EndpointRequest<'a>) -> tide::Result {
     let ManifestRequest { manifest_type } = req.body_json().await?;
     let url = match manifest_type {
-        ManifestTypes::MojangVersionManifest => {
-            "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json"
-        }
-        ManifestTypes::PrismVersionManifest => {
-            "https://meta.prismlauncher.org/v1/net.minecraft/index.json"
-        }
+        ManifestTypes::MojangVersionManifest => MetaProviders::Mojang.manifest_url(),
+        ManifestTypes::PrismVersionManifest => MetaProviders::Prism.manifest_url(),
     };
 
     let result;
     let code;
 
-    match surf::get(url).await {
-        Ok(mut response) => match response.body_json::<serde_json::Value>().await {
-            Ok(data) => {
-                result = data;
-                code = 200;
-            }
-            Err(_) => {
-                result = json!({ "message": "Failed to parse JSON" });
-                code = 500;
-            }
-        },
-
+    // Mirrors are applied by the download utils
+    match download_in_json(url).await {
+        Ok(data) => {
+            result = data;
+            code = 200;
+        }
         Err(_) => {
             result = json!({ "message": "Failed to download versions manifest" });
             code = 500;
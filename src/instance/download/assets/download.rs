@@ -8,7 +8,7 @@ use super::*;
 
 impl<'a> AssetsData<'a> {
     pub async fn process_futures(
-        futures: &mut FuturesUnordered<async_std::task::JoinHandle<std::option::Option<AssetInfo>>>,
+        futures: &mut FuturesUnordered<async_std::task::JoinHandle<std::result::Result<AssetInfo, String>>>,
         downloaded_assets: &mut HashSet<AssetInfo>,
         max: usize,
         ws_status: OperationWsMessageLocked<'a>,
@@ -16,19 +16,21 @@ impl<'a> AssetsData<'a> {
         let mut ws_status = ws_status;
 
         while let Some(result) = futures.next().await {
-            if let Some(asset_info) = result {
-                ws_status = ws_status
-                    .update_determinable(
-                        STAGE_TYPE,
-                        Some(ProcessTarget::file(
-                            asset_info.name.clone(),
-                            FileStatus::Downloaded,
-                        )),
-                        downloaded_assets.len(),
-                        max,
-                    )
-                    .await;
+            let (name, status) = match &result {
+                Ok(asset_info) => (asset_info.name.clone(), FileStatus::Downloaded),
+                Err(name) => (name.clone(), FileStatus::FailedToDownload),
+            };
 
+            ws_status = ws_status
+                .update_determinable(
+                    STAGE_TYPE,
+                    Some(ProcessTarget::file(name, status)),
+                    downloaded_assets.len(),
+                    max,
+                )
+                .await;
+
+            if let Ok(asset_info) = result {
                 downloaded_assets.insert(asset_info);
             }
         }
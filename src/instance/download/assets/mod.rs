@@ -6,14 +6,15 @@ use thiserror::Error;
 
 use crate::{
     data::db::Database, instance::
-        websocket::{OperationWsExt, OperationWsMessageLocked}, utils::download::Downloadable, websocket::messages::operation::
-        stage::{OperationStage, StageStatus}
+        websocket::{OperationWsExt, OperationWsMessageLocked}, utils::{cancel::CancellationToken, download::Downloadable}, websocket::messages::operation::
+        stage::{FailedFile, OperationStage, StageStatus}
 
 };
 
 mod parse;
 mod download;
 mod register;
+mod verify;
 
 const STAGE_TYPE: OperationStage = OperationStage::DownloadAssets;
 
@@ -21,7 +22,10 @@ pub struct AssetsData<'a> {
     manifest: &'a serde_json::Value,
     assets_path: String,
     ws_status: OperationWsMessageLocked<'a>,
-    db: &'a Database
+    db: &'a Database,
+    cancel: CancellationToken,
+    /// Don't download missing assets
+    offline: bool
 }
 
 #[derive(Debug, Getters, Default)]
@@ -50,7 +54,16 @@ impl Downloadable for AssetInfo {
 #[derive(Debug, Error)]
 pub enum AssetSyncError {
     #[error("Failed to register a new asset to DB: {0}")]
-    RegisterFailed(String)
+    RegisterFailed(String),
+
+    #[error("Failed to remove a corrupted asset from DB: {0}")]
+    UnregisterFailed(String),
+
+    #[error("Assets sync was cancelled")]
+    Cancelled,
+
+    #[error("{} assets are not downloaded and can't be fetched offline", .0.len())]
+    MissingOffline(Vec<FailedFile>),
 }
 
 
@@ -59,8 +72,11 @@ impl<'a> AssetsData<'a> {
         manifest: &'a serde_json::Value,
         assets_path: T,
         ws_status: OperationWsMessageLocked<'a>,
-        db: &'a Database
-    ) where
+        db: &'a Database,
+        offline: bool,
+        cancel: &CancellationToken
+    ) -> Result<(), AssetSyncError>
+    where
         T: AsRef<Path>,
     {
         ws_status
@@ -72,17 +88,38 @@ impl<'a> AssetsData<'a> {
             manifest,
             assets_path: assets_path.as_ref().display().to_string(),
             ws_status: ws_status.clone(),
-            db
+            db,
+            cancel: cancel.clone(),
+            offline
+        };
+
+        let missing = match Self::extract_manifest_assets(&assets_data).await {
+            Ok(missing) => missing,
+            Err(e) => {
+                println!("{e}");
+                Vec::new()
+            }
         };
 
-        match Self::extract_manifest_assets(&assets_data).await {
-            Ok(_) => (),
-            Err(e) => println!("{e}")
+        if cancel.is_cancelled() {
+            return Err(AssetSyncError::Cancelled);
+        }
+
+        if !missing.is_empty() {
+            let error = AssetSyncError::MissingOffline(missing);
+
+            ws_status
+                .complete_stage(StageStatus::Failed, STAGE_TYPE, 0.0, Some((&error).into()))
+                .await;
+
+            return Err(error);
         }
 
         ws_status
             .complete_stage(StageStatus::Completed, STAGE_TYPE, 0.0, None)
             .await;
+
+        Ok(())
     }
 }
 
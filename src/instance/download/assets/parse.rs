@@ -2,21 +2,33 @@ use std::{collections::HashSet, path::PathBuf, sync::Arc};
 
 use async_std::task;
 
-use crate::utils::download::{buffer::BufferPool, Download};
+use crate::{
+    utils::download::{
+        buffer::BufferPool, retry::RetryPolicy, scheduler::DownloadScheduler, Download,
+    },
+    websocket::messages::operation::{
+        process::{FileStatus, ProcessTarget},
+        stage::FailedFile,
+    },
+};
 
 use super::*;
 
 const ASSETS_BASE_URL: &'static str = "https://resources.download.minecraft.net";
-const CONCURRENT_TASKS_COUNT: usize = 100;
 const CONCURRENT_BUFFERS_SIZE: usize = 16 * 1024; // 16 KiB each
 
 impl<'a> AssetsData<'a> {
-    pub async fn extract_manifest_assets(&self) -> Result<(), String> {
+    /// Downloads missing assets. Assets which can't be fetched offline are returned
+    pub async fn extract_manifest_assets(&self) -> Result<Vec<FailedFile>, String> {
         let mut downloaded_assets: HashSet<AssetInfo> = HashSet::new();
+        let mut missing_offline: Vec<FailedFile> = Vec::new();
+        let concurrent_tasks_count = DownloadScheduler::global().limits().max_concurrent.max(1);
         let download_buffer_pool = Arc::new(BufferPool::new(
-            CONCURRENT_TASKS_COUNT,
+            concurrent_tasks_count,
             CONCURRENT_BUFFERS_SIZE
         ));
+        // All assets are retried the same way, even if the policy is changed meanwhile
+        let retry_policy = RetryPolicy::global();
 
         let assets_dir_pathbuf = PathBuf::from(&self.assets_path);
         let assets_dir = Arc::new(&assets_dir_pathbuf);
@@ -40,6 +52,10 @@ impl<'a> AssetsData<'a> {
             println!("Checking for assets...");
 
             for (name, v) in objects {
+                if self.cancel.is_cancelled() {
+                    break;
+                }
+
                 let hash = match v["hash"].as_str() {
                     Some(h) => h,
                     None => continue,
@@ -51,27 +67,49 @@ impl<'a> AssetsData<'a> {
 
                 let name = name.to_string();
                 let (url, relative_save_path) = Self::construct_asset_url(hash);
+
+                if self.offline {
+                    Arc::clone(&self.ws_status)
+                        .update_determinable(
+                            STAGE_TYPE,
+                            Some(ProcessTarget::file(name.clone(), FileStatus::FailedToDownload)),
+                            downloaded_assets.len(),
+                            objects.len(),
+                        )
+                        .await;
+                    missing_offline.push(FailedFile {
+                        name,
+                        url,
+                        reason: "Asset is not downloaded and can't be fetched offline".to_string(),
+                    });
+                    continue;
+                }
+
                 let save_path = assets_dir.join(relative_save_path);
 
+                let asset_name = name.clone();
                 let asset_info = AssetInfo {
                     name,
                     hash: hash.to_string(),
                     url
                 };
 
-                let dl = Download::new(save_path, asset_info, Arc::clone(&download_buffer_pool));
+                let dl = Download::new(save_path, asset_info, Arc::clone(&download_buffer_pool))
+                    .with_cancel(self.cancel.clone())
+                    .with_retry(retry_policy.clone());
 
                 futures.push(task::spawn(async move {
+                    // Retries are already exhausted here
                     match dl.download_with_checksum().await {
-                        Ok(asset_info) => Some(asset_info),
+                        Ok(asset_info) => Ok(asset_info),
                         Err(e) => {
-                            println!("{e}");
-                            None
+                            eprintln!("Failed to download {}: {}", asset_name, e);
+                            Err(asset_name)
                         }
                     }
                 }));
 
-                if futures.len() >= 100 {
+                if futures.len() >= concurrent_tasks_count {
                     Self::process_futures(
                         &mut futures,
                         &mut downloaded_assets,
@@ -92,14 +130,14 @@ impl<'a> AssetsData<'a> {
         }
 
         match Self::register_assets(&self, &mut downloaded_assets).await {
-            Ok(_) => Ok(()),
+            Ok(_) => Ok(missing_offline),
             Err(e) => Err(e.to_string())
         }
     }
 
     /// Construct the url by hash and domain
     /// Retrieves url to download and relative path for a file saving
-    fn construct_asset_url(hash: &str) -> (String, String) {
+    pub(super) fn construct_asset_url(hash: &str) -> (String, String) {
         let short_hash = &hash[..2];
 
         (
@@ -0,0 +1,76 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use crate::{
+    utils::download::file_sha1,
+    websocket::messages::operation::process::{FileStatus, ProcessTarget},
+};
+
+use super::*;
+
+impl<'a> AssetsData<'a> {
+    /// Re-hashes downloaded asset objects of the manifest.
+    /// Missing and corrupted objects are removed from DB,
+    /// so the following sync downloads them again
+    pub async fn verify_assets<T>(
+        manifest: &'a serde_json::Value,
+        assets_path: T,
+        ws_status: OperationWsMessageLocked<'a>,
+        db: &'a Database,
+        cancel: &CancellationToken,
+    ) -> Result<(), AssetSyncError>
+    where
+        T: AsRef<Path>,
+    {
+        let objects = match manifest["objects"].as_object() {
+            Some(objects) => objects,
+            None => return Ok(()),
+        };
+
+        let total = objects.len();
+        let mut checked = HashSet::new();
+        let mut ws_status = ws_status;
+
+        for (current, (name, v)) in objects.iter().enumerate() {
+            if cancel.is_cancelled() {
+                return Err(AssetSyncError::Cancelled);
+            }
+
+            let hash = match v["hash"].as_str() {
+                Some(h) => h,
+                None => continue,
+            };
+
+            // Same object can be used by several names
+            if !checked.insert(hash) {
+                continue;
+            }
+
+            let (_, relative_save_path) = Self::construct_asset_url(hash);
+            let path = PathBuf::from(assets_path.as_ref()).join(relative_save_path);
+
+            let status = match file_sha1(&path).await {
+                Ok(calculated) if calculated == hash => FileStatus::Verified,
+                _ => {
+                    sqlx::query!("DELETE FROM assets WHERE hash = ?1", hash)
+                        .execute(&db.pool)
+                        .await
+                        .map_err(|e| AssetSyncError::UnregisterFailed(e.to_string()))?;
+
+                    println!("Corrupted asset: {}", name);
+                    FileStatus::Corrupted
+                }
+            };
+
+            ws_status = ws_status
+                .update_determinable(
+                    OperationStage::VerifyFiles,
+                    Some(ProcessTarget::file(name.clone(), status)),
+                    current + 1,
+                    total,
+                )
+                .await;
+        }
+
+        Ok(())
+    }
+}
@@ -1,16 +1,22 @@
 use std::collections::HashSet;
 
+use async_channel::unbounded;
 use async_std::task;
 use futures::{stream::FuturesUnordered, StreamExt};
 
 use crate::{
-    utils::download::{buffer::BufferPool, Download},
-    websocket::messages::operation::process::{FileStatus, ProcessTarget},
+    instance::websocket::forward_download_progress,
+    utils::download::{
+        buffer::BufferPool, retry::RetryPolicy, scheduler::DownloadScheduler, Download,
+    },
+    websocket::messages::operation::{
+        process::{FileStatus, ProcessTarget},
+        stage::FailedFile,
+    },
 };
 
 use super::*;
 
-const CONCURRENT_TASKS_COUNT: usize = 100;
 const CONCURRENT_BUFFERS_SIZE: usize = 16 * 1024; // 16 KiB each
 
 impl<'a, 'b> LibsData<'a, 'b> {
@@ -18,17 +24,24 @@ impl<'a, 'b> LibsData<'a, 'b> {
         downloadable_libs: Vec<LibInfo>,
         ws_status: OperationWsMessageLocked<'a>,
         db: &'a db::Database,
+        offline: bool,
+        cancel: &CancellationToken,
     ) -> Result<SyncResult, String> {
         let downloadable_libs_count = downloadable_libs.len();
         let mut downloaded_libs: HashSet<LibInfo> = HashSet::new();
         let mut classpaths = Vec::new();
         let mut natives_paths: Vec<PathBuf> = Vec::new();
+        let mut failed: Vec<FailedFile> = Vec::new();
 
+        // Connections are limited by the scheduler, spawning more tasks is useless
+        let concurrent_tasks_count = DownloadScheduler::global().limits().max_concurrent.max(1);
         let mut futures = FuturesUnordered::new();
         let download_buffers_pool = Arc::new(BufferPool::new(
-            CONCURRENT_TASKS_COUNT,
+            concurrent_tasks_count,
             CONCURRENT_BUFFERS_SIZE,
         ));
+        // All libs are retried the same way, even if the policy is changed meanwhile
+        let retry_policy = RetryPolicy::global();
 
         let cached = match sqlx::query_as!(
             LibInfo,
@@ -51,45 +64,95 @@ impl<'a, 'b> LibsData<'a, 'b> {
             &mut classpaths,
             &mut natives_paths,
         );
-        for missing_lib in missing.into_iter() {
-            let current_buffer = Arc::clone(&download_buffers_pool);
-
-            let download_info = Download::new(
-                PathBuf::from(missing_lib.path.clone()),
-                missing_lib,
-                current_buffer,
-            );
-
-            futures.push(task::spawn(async move {
-                match download_info.download_with_checksum().await {
-                    Ok(lib_info) => Some(lib_info),
-                    Err(e) => {
-                        println!("{e}");
-                        None
-                    }
-                }
+
+        // Missing libs are reported as failed, so the game isn't launched without them
+        if offline {
+            failed.extend(missing.into_iter().map(|lib| FailedFile {
+                name: lib.name,
+                url: lib.url,
+                reason: "Library is not downloaded and can't be fetched offline".to_string(),
             }));
 
-            if futures.len() >= CONCURRENT_TASKS_COUNT {
-                Self::process_futures(
-                    &mut futures,
-                    &mut downloaded_libs,
-                    downloadable_libs_count,
-                    ws_status.clone(),
+            return Ok(SyncResult {
+                classpaths,
+                natives_paths,
+                failed,
+            });
+        }
+
+        // Byte progress of large files like the client jar
+        let (progress_sender, progress_receiver) = unbounded();
+        let downloads = async {
+            for missing_lib in missing.into_iter() {
+                if cancel.is_cancelled() {
+                    break;
+                }
+
+                let current_buffer = Arc::clone(&download_buffers_pool);
+                let name = missing_lib.name.clone();
+                let url = missing_lib.url.clone();
+
+                let download_info = Download::new(
+                    PathBuf::from(missing_lib.path.clone()),
+                    missing_lib,
+                    current_buffer,
                 )
-                .await;
+                .with_cancel(cancel.clone())
+                .with_progress(progress_sender.clone())
+                .with_retry(retry_policy.clone());
+
+                futures.push(task::spawn(async move {
+                    // Retries are already exhausted here
+                    match download_info.download_with_checksum().await {
+                        Ok(lib_info) => Ok(lib_info),
+                        Err(e) => {
+                            eprintln!("Failed to download {}: {}", name, e);
+                            Err(FailedFile {
+                                name,
+                                url,
+                                reason: e,
+                            })
+                        }
+                    }
+                }));
+
+                if futures.len() >= concurrent_tasks_count {
+                    Self::process_futures(
+                        &mut futures,
+                        &mut downloaded_libs,
+                        &mut failed,
+                        downloadable_libs_count,
+                        ws_status.clone(),
+                    )
+                    .await;
+                }
             }
-        }
 
-        Self::process_futures(
-            &mut futures,
-            &mut downloaded_libs,
-            downloadable_libs_count,
-            ws_status,
-        )
-        .await;
+            Self::process_futures(
+                &mut futures,
+                &mut downloaded_libs,
+                &mut failed,
+                downloadable_libs_count,
+                ws_status.clone(),
+            )
+            .await;
+
+            // Forwarding of the progress is finished with the last sender
+            drop(progress_sender);
+        };
+        futures::join!(
+            downloads,
+            forward_download_progress(ws_status.clone(), STAGE_TYPE, progress_receiver)
+        );
+
+        // Register libs which were fully downloaded before
+        // the cancellation, so they won't be downloaded again
+        let registered = Self::register_libs(&mut downloaded_libs, db).await;
+        if cancel.is_cancelled() {
+            return Err(LibsSyncError::Cancelled.to_string());
+        }
 
-        match Self::register_libs(&mut downloaded_libs, &db).await {
+        match registered {
             Ok((mut downloaded_classpaths, mut downloaded_natives_paths)) => {
                 classpaths.append(&mut downloaded_classpaths);
                 natives_paths.append(&mut downloaded_natives_paths);
@@ -97,6 +160,7 @@ impl<'a, 'b> LibsData<'a, 'b> {
                 Ok(SyncResult {
                     classpaths,
                     natives_paths,
+                    failed,
                 })
             }
             Err(e) => Err(e.to_string()),
@@ -138,29 +202,35 @@ impl<'a, 'b> LibsData<'a, 'b> {
 
     async fn process_futures(
         futures: &mut futures::stream::FuturesUnordered<
-            async_std::task::JoinHandle<std::option::Option<LibInfo>>,
+            async_std::task::JoinHandle<std::result::Result<LibInfo, FailedFile>>,
         >,
         downloaded_libraries: &mut HashSet<LibInfo>,
+        failed_libraries: &mut Vec<FailedFile>,
         max: usize,
         ws_status: OperationWsMessageLocked<'a>,
     ) {
         let mut ws_status = ws_status;
 
         while let Some(result) = futures.next().await {
-            if let Some(lib_info) = result {
-                ws_status = ws_status
-                    .update_determinable(
-                        STAGE_TYPE,
-                        Some(ProcessTarget::file(
-                            lib_info.name.clone(),
-                            FileStatus::Downloaded,
-                        )),
-                        downloaded_libraries.len(),
-                        max,
-                    )
-                    .await;
+            let (name, status) = match &result {
+                Ok(lib_info) => (lib_info.name.clone(), FileStatus::Downloaded),
+                Err(failed) => (failed.name.clone(), FileStatus::FailedToDownload),
+            };
+
+            ws_status = ws_status
+                .update_determinable(
+                    STAGE_TYPE,
+                    Some(ProcessTarget::file(name, status)),
+                    downloaded_libraries.len(),
+                    max,
+                )
+                .await;
 
-                downloaded_libraries.insert(lib_info);
+            match result {
+                Ok(lib_info) => {
+                    downloaded_libraries.insert(lib_info);
+                }
+                Err(failed) => failed_libraries.push(failed),
             }
         }
     }
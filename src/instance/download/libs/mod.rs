@@ -10,13 +10,15 @@ use crate::{
         paths::InstancePaths,
         websocket::{OperationWsExt, OperationWsMessageLocked},
     },
-    utils::{download::Downloadable, maven},
-    websocket::messages::operation::stage::{OperationStage, StageStatus},
+    manifest::rules::RuleContext,
+    utils::{cancel::CancellationToken, download::Downloadable, maven},
+    websocket::messages::operation::stage::{FailedFile, OperationStage, StageStatus},
 };
 
 mod download;
 mod parse;
 mod register;
+mod verify;
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum ManifestType {
@@ -24,6 +26,17 @@ pub enum ManifestType {
     Prism,
 }
 
+/// What is done with the libs found in the manifest
+#[derive(Clone, Copy, PartialEq)]
+enum SyncMode {
+    /// Download missing libs
+    Download,
+    /// Use saved manifests and report missing libs as failed
+    Offline,
+    /// Only check downloaded libs instead of downloading missing ones
+    Verify,
+}
+
 #[derive(Error, Debug)]
 pub enum LibsSyncError {
     #[error("OS is not supported")]
@@ -31,12 +44,31 @@ pub enum LibsSyncError {
 
     #[error("CPU architecture is not supported")]
     ArchNotAvailable,
+
+    #[error("Libs sync was cancelled")]
+    Cancelled,
+
+    #[error("{0}")]
+    SyncFailed(String),
+
+    #[error("Failed to download {} libraries: {}", .0.len(), failed_names(.0))]
+    DownloadFailed(Vec<FailedFile>),
+}
+
+fn failed_names(failed: &[FailedFile]) -> String {
+    failed
+        .iter()
+        .map(|file| file.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 #[derive(Debug)]
 pub struct SyncResult {
     classpaths: Vec<String>,
     natives_paths: Vec<PathBuf>,
+    /// Libs which could not be downloaded
+    failed: Vec<FailedFile>,
 }
 
 pub struct LibsData<'a, 'b> {
@@ -45,6 +77,9 @@ pub struct LibsData<'a, 'b> {
     ws_status: OperationWsMessageLocked<'a>,
     db: &'a db::Database,
     current_os: &'a str,
+    rules: RuleContext,
+    cancel: CancellationToken,
+    mode: SyncMode,
 }
 
 #[derive(Eq, Hash, PartialEq, Debug, Clone, Deserialize, sqlx::FromRow, Getters)]
@@ -83,42 +118,115 @@ impl<'a, 'b> LibsData<'a, 'b> {
         ws_status: OperationWsMessageLocked<'a>,
         db: &'a db::Database,
         manifest_type: ManifestType,
-    ) -> Result<SyncResult, String> {
+        offline: bool,
+        cancel: &CancellationToken,
+    ) -> Result<SyncResult, LibsSyncError> {
         // Sync status through WebSocket
         ws_status
             .clone()
             .start_stage_determinable(STAGE_TYPE, None, 0, 0)
             .await;
 
+        let mode = if offline {
+            SyncMode::Offline
+        } else {
+            SyncMode::Download
+        };
+        let sync_data = Self::parse(manifest, paths, ws_status.clone(), db, manifest_type, cancel, mode)
+            .await
+            .map_err(LibsSyncError::SyncFailed)?;
+
+        // Classpath without these libs would crash the game on launch
+        if !sync_data.failed.is_empty() {
+            let error = LibsSyncError::DownloadFailed(sync_data.failed);
+
+            ws_status
+                .complete_stage(StageStatus::Failed, STAGE_TYPE, 0.0, Some((&error).into()))
+                .await;
+
+            return Err(error);
+        }
+
+        ws_status
+            .complete_stage(StageStatus::Completed, STAGE_TYPE, 0.0, None)
+            .await;
+
+        Ok(sync_data)
+    }
+
+    /// Re-hashes downloaded libs needed by the manifest, including natives
+    /// and the client jar. Missing and corrupted libs are removed from DB,
+    /// so the following sync downloads them again
+    pub async fn verify_libs(
+        manifest: &'b serde_json::Value,
+        paths: &'a InstancePaths,
+        ws_status: OperationWsMessageLocked<'a>,
+        db: &'a db::Database,
+        manifest_type: ManifestType,
+        cancel: &CancellationToken,
+    ) -> Result<(), String> {
+        Self::parse(manifest, paths, ws_status, db, manifest_type, cancel, SyncMode::Verify).await?;
+        Ok(())
+    }
+
+    async fn parse(
+        manifest: &'b serde_json::Value,
+        paths: &'a InstancePaths,
+        ws_status: OperationWsMessageLocked<'a>,
+        db: &'a db::Database,
+        manifest_type: ManifestType,
+        cancel: &CancellationToken,
+        mode: SyncMode,
+    ) -> Result<SyncResult, String> {
         let current_os = match construct_os_name() {
             Ok(name) => name,
             Err(e) => return Err(e.to_string()),
         };
 
+        // Prism meta names platforms in rules like natives classifiers
+        let rules = match manifest_type {
+            ManifestType::Official => RuleContext::current(),
+            ManifestType::Prism => RuleContext::current().with_os_name(current_os),
+        };
+
         let paths = Arc::new(paths);
         let libs_data = LibsData {
             manifest,
             paths,
-            ws_status: ws_status.clone(),
+            ws_status,
             db,
             current_os,
+            rules,
+            cancel: cancel.clone(),
+            mode,
         };
 
-        let result = match manifest_type {
+        match manifest_type {
             ManifestType::Official => Self::parse_manifest_official(&libs_data).await,
             ManifestType::Prism => Self::parse_manifest_prism(&libs_data).await,
-        };
-
-        let sync_data = match result {
-            Ok(data) => data,
-            Err(e) => return Err(e),
-        };
+        }
+    }
 
-        ws_status
-            .complete_stage(StageStatus::Completed, STAGE_TYPE, 0.0, None)
+    /// Handles libs found by the parser
+    async fn process_parsed_libs(&self, libs: Vec<LibInfo>) -> Result<SyncResult, String> {
+        if self.mode == SyncMode::Verify {
+            return Self::verify_downloaded_libs(
+                libs,
+                Arc::clone(&self.ws_status),
+                self.db,
+                &self.cancel,
+            )
             .await;
+        }
 
-        Ok(sync_data)
+        Self::download_missing_libs(
+            libs,
+            Arc::clone(&self.ws_status),
+            self.db,
+            self.mode == SyncMode::Offline,
+            &self.cancel,
+        )
+        .await
     }
 
     pub fn get_classpaths_mut(result: &mut SyncResult) -> &mut Vec<String> {
@@ -1,5 +1,3 @@
-use std::sync::Arc;
-
 use crate::instance::download::libs::{LibInfo, LibsData, SyncResult};
 
 mod prism;
@@ -15,26 +13,7 @@ impl<'a, 'b> LibsData<'a, 'b> {
             for lib in libraries {
                 let lib_name = lib["name"].as_str();
 
-                let allow_lib = if let Some(rules) = lib["rules"].as_array() {
-                    rules.iter().any(|rule| {
-                        if let Some(action) = rule["action"].as_str() {
-                            if action == "allow" {
-                                if let Some(os) = rule["os"].as_object() {
-                                    if os.get("name").and_then(|name| name.as_str())
-                                        == Some(self.current_os)
-                                    {
-                                        return true;
-                                    }
-                                } else {
-                                    return true;
-                                }
-                            }
-                        }
-                        false
-                    })
-                } else {
-                    true
-                };
+                let allow_lib = self.rules.allows(lib["rules"].as_array());
 
                 if allow_lib {
                     let lib_path = lib["downloads"]["artifact"]["path"].as_str();
@@ -107,11 +86,6 @@ impl<'a, 'b> LibsData<'a, 'b> {
             });
         }
 
-        Self::download_missing_libs(
-            downloadable_libs,
-            Arc::clone(&self.ws_status),
-            &self.db,
-        )
-        .await
+        self.process_parsed_libs(downloadable_libs).await
     }
 }
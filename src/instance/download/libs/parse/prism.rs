@@ -1,13 +1,12 @@
-use std::{path::PathBuf, sync::Arc};
-
-use serde_json::Value;
+use std::path::PathBuf;
 
 use crate::{
     instance::download::{
-        libs::{LibInfo, SyncResult},
-        manifest::download_manifest,
+        libs::{LibInfo, SyncMode, SyncResult},
+        manifest::get_manifest,
     },
     utils::str_nth_occurrence,
+    websocket::messages::operation::stage::FailedFile,
 };
 
 use super::*;
@@ -26,7 +25,7 @@ impl<'a, 'b> LibsData<'a, 'b> {
                         // If artifact not present or incomplete, check rules & downloads.classifiers under lib
                         let natives_key = format!("natives-{}", self.current_os);
                         if let Some(rules) = lib.get("rules").and_then(|v| v.as_array()) {
-                            if rules_applies(rules, self.current_os) {
+                            if self.rules.allows(Some(rules)) {
                                 if let Some(native_obj) = downloads_val
                                     .get("classifiers")
                                     .and_then(|c| c.get(&natives_key))
@@ -35,7 +34,7 @@ impl<'a, 'b> LibsData<'a, 'b> {
                                         native_obj.get("sha1").and_then(|v| v.as_str()),
                                         native_obj.get("url").and_then(|v| v.as_str()),
                                     ) {
-                                        if let Some(path) = get_path_from_url(url) {
+                                        if let Some(path) = get_artifact_path(native_obj, url) {
                                             downloadable_libs.push(LibInfo {
                                                 hash: sha1.to_string(),
                                                 name: name.to_string(),
@@ -52,7 +51,7 @@ impl<'a, 'b> LibsData<'a, 'b> {
                                             artifact.get("url").and_then(|v| v.as_str()),
                                             artifact.get("sha1").and_then(|v| v.as_str()),
                                         ) {
-                                            if let Some(path) = get_path_from_url(url) {
+                                            if let Some(path) = get_artifact_path(artifact, url) {
                                                 downloadable_libs.push(LibInfo {
                                                     hash: sha1.to_string(),
                                                     name: name.to_string(),
@@ -73,7 +72,7 @@ impl<'a, 'b> LibsData<'a, 'b> {
                                         native_val.get("url").and_then(|v| v.as_str()),
                                         native_val.get("sha1").and_then(|v| v.as_str()),
                                     ) {
-                                        if let Some(path) = get_path_from_url(url) {
+                                        if let Some(path) = get_artifact_path(native_val, url) {
                                             downloadable_libs.push(LibInfo {
                                                 hash: sha1.to_string(),
                                                 name: name.to_string(),
@@ -91,7 +90,7 @@ impl<'a, 'b> LibsData<'a, 'b> {
                                         artifact.get("url").and_then(|v| v.as_str()),
                                         artifact.get("sha1").and_then(|v| v.as_str()),
                                     ) {
-                                        if let Some(path) = get_path_from_url(url) {
+                                        if let Some(path) = get_artifact_path(artifact, url) {
                                             downloadable_libs.push(LibInfo {
                                                 hash: sha1.to_string(),
                                                 name: name.to_string(),
@@ -112,6 +111,7 @@ impl<'a, 'b> LibsData<'a, 'b> {
         // Check for modern builded libs
         let mut additional_classpaths: Vec<String> = Vec::new();
         let mut additional_natives_paths: Vec<PathBuf> = Vec::new();
+        let mut additional_failed: Vec<FailedFile> = Vec::new();
         if let Some(requires) = self.manifest.get("requires").and_then(|v| v.as_array()) {
             for req in requires {
                 if let (Some(suggests), Some(uid)) = (
@@ -120,7 +120,9 @@ impl<'a, 'b> LibsData<'a, 'b> {
                 ) {
                     // Parse another page
                     let url = format!("{}{}/{}.json", META_BASE_URL, uid, suggests);
-                    let manifest = match download_manifest::<String>(&url, None).await {
+                    let save_path = self.paths.meta().join(uid);
+                    let offline = self.mode == SyncMode::Offline;
+                    let manifest = match get_manifest(&url, save_path, offline).await {
                         Ok(data) => data.0,
                         Err(e) => return Err(e.to_string()),
                     };
@@ -131,6 +133,9 @@ impl<'a, 'b> LibsData<'a, 'b> {
                         ws_status: self.ws_status.clone(),
                         db: self.db,
                         current_os: self.current_os,
+                        rules: self.rules.clone(),
+                        cancel: self.cancel.clone(),
+                        mode: self.mode,
                     };
 
                     match Box::pin(LibsData::parse_manifest_prism(&libs_data)).await {
@@ -144,6 +149,8 @@ impl<'a, 'b> LibsData<'a, 'b> {
                             if !result.natives_paths.is_empty() {
                                 additional_natives_paths.append(&mut result.natives_paths);
                             }
+
+                            additional_failed.append(&mut result.failed);
                         }
                         Err(e) => return Err(e),
                     };
@@ -181,16 +188,11 @@ impl<'a, 'b> LibsData<'a, 'b> {
             }
         }
 
-        match Self::download_missing_libs(
-            downloadable_libs,
-            Arc::clone(&self.ws_status),
-            self.db,
-        )
-        .await
-        {
+        match self.process_parsed_libs(downloadable_libs).await {
             Ok(mut result) => {
                 result.classpaths.append(&mut additional_classpaths);
                 result.natives_paths.append(&mut additional_natives_paths);
+                result.failed.append(&mut additional_failed);
                 Ok(result)
             }
             Err(e) => Err(e.to_string()),
@@ -198,37 +200,13 @@ impl<'a, 'b> LibsData<'a, 'b> {
     }
 }
 
-fn rules_applies(rules: &Vec<Value>, current_os: &str) -> bool {
-    let mut allow_lib = false;
-
-    for rule in rules {
-        let os_obj = match rule.get("os") {
-            Some(os) => os,
-            None => {
-                if let Some(action) = rule.get("action").and_then(|v| v.as_str()) {
-                    if action == "allow" {
-                        allow_lib = true;
-                    }
-                }
-
-                continue;
-            }
-        };
-
-        if let Some(os_name) = os_obj.get("name").and_then(|v| v.as_str()) {
-            if os_name == current_os {
-                if let Some(action) = rule.get("action").and_then(|v| v.as_str()) {
-                    if action == "allow" {
-                        return true;
-                    } else {
-                        return false;
-                    }
-                }
-            }
-        }
+/// Path of the artifact in the libs dir. Manifests which don't set it
+/// host their libs at the root of the repository
+fn get_artifact_path<'v>(artifact: &'v serde_json::Value, url: &'v str) -> Option<&'v str> {
+    match artifact.get("path").and_then(|v| v.as_str()) {
+        Some(path) => Some(path),
+        None => get_path_from_url(url),
     }
-
-    allow_lib
 }
 
 fn get_path_from_url(url: &str) -> Option<&str> {
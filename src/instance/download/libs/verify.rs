@@ -0,0 +1,56 @@
+use std::path::Path;
+
+use crate::{
+    utils::download::file_sha1,
+    websocket::messages::operation::process::{FileStatus, ProcessTarget},
+};
+
+use super::*;
+
+impl<'a, 'b> LibsData<'a, 'b> {
+    pub async fn verify_downloaded_libs(
+        libs: Vec<LibInfo>,
+        ws_status: OperationWsMessageLocked<'a>,
+        db: &'a db::Database,
+        cancel: &CancellationToken,
+    ) -> Result<SyncResult, String> {
+        let total = libs.len();
+        let mut ws_status = ws_status;
+
+        for (current, lib) in libs.into_iter().enumerate() {
+            if cancel.is_cancelled() {
+                return Err(LibsSyncError::Cancelled.to_string());
+            }
+
+            let status = match file_sha1(Path::new(&lib.path)).await {
+                Ok(hash) if hash == lib.hash.to_lowercase() => FileStatus::Verified,
+                _ => {
+                    // Lib is downloaded again on the next sync
+                    sqlx::query!("DELETE FROM libraries WHERE hash = ?1", lib.hash)
+                        .execute(&db.pool)
+                        .await
+                        .map_err(|e| e.to_string())?;
+
+                    println!("Corrupted lib: {}", lib.path);
+                    FileStatus::Corrupted
+                }
+            };
+
+            ws_status = ws_status
+                .update_determinable(
+                    OperationStage::VerifyFiles,
+                    Some(ProcessTarget::file(lib.name, status)),
+                    current + 1,
+                    total,
+                )
+                .await;
+        }
+
+        // Classpaths are retrieved by the following sync
+        Ok(SyncResult {
+            classpaths: Vec::new(),
+            natives_paths: Vec::new(),
+            failed: Vec::new(),
+        })
+    }
+}
@@ -5,13 +5,76 @@ use async_std::{fs::{self, File}, io::WriteExt};
 
 use crate::utils::download::download_in_json;
 
+#[cfg(test)]
+mod tests;
+
 #[derive(Error, Debug)]
 pub enum DownloadError {
     #[error("Failed to write file or create directory: {0}. Target path: {1}")]
     WriteFailed(String, String),
 
     #[error("Download failed: {0}")]
-    OtherError(String)
+    OtherError(String),
+
+    #[error("Manifest {0} is not downloaded yet and can't be fetched offline")]
+    NotCached(String),
+}
+
+/// Path the manifest downloaded from `url` is saved to
+fn cached_manifest_path<P: AsRef<Path>>(url: &str, save_path: P) -> String {
+    let name_start_pos = url.rfind('/').unwrap_or(0);
+    format!("{}{}", save_path.as_ref().display(), &url[name_start_pos..])
+}
+
+/// Reads the manifest saved by the previous `download_manifest`
+async fn load_cached_manifest<P>(
+    url: &str,
+    save_path: P
+) -> Result<(serde_json::Value, Option<String>), DownloadError>
+where
+    P: AsRef<Path>
+{
+    let full_path = cached_manifest_path(url, save_path);
+
+    let data = match fs::read_to_string(&full_path).await {
+        Ok(data) => data,
+        Err(_) => return Err(DownloadError::NotCached(full_path)),
+    };
+
+    match serde_json::from_str(&data) {
+        Ok(manifest) => Ok((manifest, Some(full_path))),
+        Err(e) => Err(DownloadError::OtherError(format!("Cached manifest {} is broken: {}", full_path, e))),
+    }
+}
+
+/// Downloads the manifest, falling back to the saved one when the network
+/// is unavailable. In the `offline` mode only the saved manifest is used
+pub async fn get_manifest<P>(
+    url: &str,
+    save_path: P,
+    offline: bool
+) -> Result<(serde_json::Value, Option<String>), DownloadError>
+where
+    P: AsRef<Path>
+{
+    if offline {
+        return load_cached_manifest(url, save_path).await;
+    }
+
+    match download_manifest(url, Some(&save_path)).await {
+        Ok(manifest) => Ok(manifest),
+        Err(DownloadError::OtherError(e)) => {
+            eprintln!("Failed to download {}, using the saved manifest: {}", url, e);
+
+            match load_cached_manifest(url, save_path).await {
+                Ok(manifest) => Ok(manifest),
+                // Network error is more useful than the missing cache
+                Err(DownloadError::NotCached(_)) => Err(DownloadError::OtherError(e)),
+                Err(cache_error) => Err(cache_error),
+            }
+        },
+        Err(e) => Err(e),
+    }
 }
 
 pub async fn download_manifest<P>(
@@ -24,8 +87,7 @@ where
     match download_in_json(url).await {
         Ok(data) => {
             if let Some(save_path) = save_path {
-                let name_start_pos = url.rfind('/').unwrap();
-                let full_path = format!("{}{}", save_path.as_ref().display(), url[name_start_pos..].to_string());
+                let full_path = cached_manifest_path(url, save_path);
                 let dir_last_pos = full_path.rfind('/').unwrap();
 
                 match fs::create_dir_all(full_path[..dir_last_pos].to_string()).await {
@@ -47,11 +109,12 @@ where
 
 pub async fn get_assets_manifest<'a>(
     version_manifest: &'a serde_json::Value,
-    assets_path: &'a str
+    assets_path: &Path,
+    offline: bool
 ) -> Result<(serde_json::Value, &'a str), String> {
     if let Some(asset_index) = version_manifest["assetIndex"].as_object() {
         if let Some(asset_url) = asset_index["url"].as_str() {
-            match download_manifest(&asset_url.to_string(), Some(&assets_path)).await {
+            match get_manifest(asset_url, assets_path, offline).await {
                 Ok(manifest) => return Ok((manifest.0, asset_index["id"].as_str().unwrap())),
                 Err(e) => return Err(format!("Failed to download assets manifest: {}", e)),
             };
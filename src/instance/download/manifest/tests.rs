@@ -0,0 +1,71 @@
+use httpmock::{Method::GET, MockServer};
+use serde_json::json;
+use tempfile::tempdir;
+
+use super::*;
+
+#[async_std::test]
+async fn offline_uses_saved_manifest() {
+    let tmp = tempdir().unwrap();
+    let url = "https://piston-meta.mojang.com/v1/packages/1.20.1.json";
+    fs::write(tmp.path().join("1.20.1.json"), r#"{"id": "1.20.1"}"#)
+        .await
+        .unwrap();
+
+    let (manifest, path) = get_manifest(url, tmp.path(), true).await.unwrap();
+
+    assert_eq!(manifest, json!({ "id": "1.20.1" }));
+    assert_eq!(path.unwrap(), format!("{}/1.20.1.json", tmp.path().display()));
+}
+
+#[async_std::test]
+async fn offline_without_saved_manifest() {
+    let tmp = tempdir().unwrap();
+    let url = "https://piston-meta.mojang.com/v1/packages/1.20.1.json";
+
+    let result = get_manifest(url, tmp.path(), true).await;
+    assert!(matches!(result, Err(DownloadError::NotCached(_))));
+}
+
+#[async_std::test]
+async fn saved_manifest_used_when_server_fails() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/1.20.1.json");
+            then.status(404);
+        })
+        .await;
+
+    let tmp = tempdir().unwrap();
+    fs::write(tmp.path().join("1.20.1.json"), r#"{"id": "1.20.1"}"#)
+        .await
+        .unwrap();
+
+    let (manifest, _) = get_manifest(&server.url("/1.20.1.json"), tmp.path(), false)
+        .await
+        .unwrap();
+    assert_eq!(manifest, json!({ "id": "1.20.1" }));
+}
+
+#[async_std::test]
+async fn downloaded_manifest_is_saved() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/1.20.1.json");
+            then.status(200).json_body(json!({ "id": "1.20.1" }));
+        })
+        .await;
+
+    let tmp = tempdir().unwrap();
+    get_manifest(&server.url("/1.20.1.json"), tmp.path(), false)
+        .await
+        .unwrap();
+
+    // Now it is available offline
+    let (manifest, _) = get_manifest(&server.url("/1.20.1.json"), tmp.path(), true)
+        .await
+        .unwrap();
+    assert_eq!(manifest, json!({ "id": "1.20.1" }));
+}
@@ -1,16 +1,28 @@
 use std::sync::Arc;
 
+use async_channel::unbounded;
 use launch::{ClientOptions, LaunchInfoBuilder};
 
 use crate::{
     instance::{
         download::{
-            assets::AssetsData,
+            assets::{AssetSyncError, AssetsData},
             libs::LibsData,
-            manifest::{download_manifest, get_assets_manifest},
-        }, launch::args::ArgType, websocket::{OperationWsExt, OperationWsMessage}
+            manifest::{get_assets_manifest, get_manifest},
+        },
+        launch::args::{ArgType, CLASSPATH_SEPARATOR},
+        loader::{processors::ProcessorsContext, resolve_loader, Loader, LoaderError},
+        websocket::{
+            forward_download_progress, OperationWsExt, OperationWsMessage,
+            OperationWsMessageLocked,
+        }
+    },
+    java::{Java, JavaRequirement},
+    utils::cancel::CancellationToken,
+    websocket::messages::operation::{
+        event::OperationStatus,
+        stage::{OperationStage, StageError, StageStatus},
     },
-    websocket::messages::operation::stage::{OperationStage, StageStatus},
 };
 
 use super::*;
@@ -23,14 +35,32 @@ impl<'a> Instance {
         req: &'b EndpointRequest<'a>,
         ws: &WebSocketConnection,
     ) -> Result<(Self, LaunchInfo)> {
-        // Init WebSocket sync task
-        let ws_status = OperationWsMessage::create_init_task(&ws, &client_data.request_id).await;
+        Self::run_init_task("Initialize instance", client_data, register, false, req, ws).await
+    }
+
+    /// Re-hashes libs, client jar, natives and assets of the instance
+    /// and downloads missing or corrupted ones
+    pub async fn verify<'b>(
+        client_data: InitData,
+        req: &'b EndpointRequest<'a>,
+        ws: &WebSocketConnection,
+    ) -> Result<(Self, LaunchInfo)> {
+        Self::run_init_task("Verify instance", client_data, false, true, req, ws).await
+    }
 
+    async fn run_init_task<'b>(
+        task_name: &'static str,
+        client_data: InitData,
+        register: bool,
+        verify: bool,
+        req: &'b EndpointRequest<'a>,
+        ws: &WebSocketConnection,
+    ) -> Result<(Self, LaunchInfo)> {
         // Init internal task
         let global_app_state = req.state();
         let task_handle = match global_app_state
             .add_task(Task::new_shared(
-                "Initialize instance",
+                task_name,
                 TaskStatus::Pending,
                 None,
                 TaskProgress::Indeterminable,
@@ -41,6 +71,61 @@ impl<'a> Instance {
             Ok(handle) => handle,
             Err(e) => return Err(InstanceError::CreationFailed(e.to_string())),
         };
+        let cancel = global_app_state.create_cancel_token(task_handle.id).await;
+
+        let request_id = client_data.request_id.clone();
+        let result =
+            Self::init_stages(client_data, register, verify, req, ws, task_handle.id, &cancel)
+                .await;
+
+        global_app_state.remove_cancel_token(task_handle.id).await;
+
+        let (task_status, operation_status) = match &result {
+            Ok(_) => (TaskStatus::Completed, OperationStatus::Completed),
+            Err(InstanceError::Cancelled) => (TaskStatus::Cancelled, OperationStatus::Cancelled),
+            Err(_) => (TaskStatus::Failed, OperationStatus::Failed),
+        };
+        let error = result.as_ref().err().map(StageError::from);
+
+        global_app_state
+            .update_task(task_handle.id, |t| {
+                t.stage = None;
+                t.status = task_status;
+            })
+            .await
+            .unwrap();
+        OperationWsMessage::finish_operation(
+            ws,
+            &request_id,
+            task_handle.id,
+            operation_status,
+            error,
+        )
+        .await;
+
+        result
+    }
+
+    async fn init_stages<'b>(
+        client_data: InitData,
+        register: bool,
+        verify: bool,
+        req: &'b EndpointRequest<'a>,
+        ws: &WebSocketConnection,
+        task_id: usize,
+        cancel: &CancellationToken,
+    ) -> Result<(Self, LaunchInfo)> {
+        // Init WebSocket sync task
+        let ws_status = OperationWsMessage::create_init_task(
+            ws,
+            &client_data.request_id,
+            task_id,
+            verify,
+            client_data.loader,
+        )
+        .await;
+
+        let global_app_state = req.state();
 
         // Get default paths
         let mut paths = paths::InstancePaths::get_required_paths(
@@ -52,6 +137,8 @@ impl<'a> Instance {
         let mut launch_builder = LaunchInfoBuilder::new();
         launch_builder.set_arg_value(ArgType::GameDir, &paths.instance());
         launch_builder.set_arg_value(ArgType::AssetsDir, &paths.assets());
+        launch_builder.set_arg_value(ArgType::LibraryDirectory, paths.libs());
+        launch_builder.set_natives_dir(paths.natives());
 
         // Sync WebSocket task and internal task
         ws_status
@@ -59,7 +146,7 @@ impl<'a> Instance {
             .start_stage_indeterminable(OperationStage::FetchManifest)
             .await;
         global_app_state
-            .update_task(task_handle.id, |t| {
+            .update_task(task_id, |t| {
                 t.status = TaskStatus::Running;
                 t.stage = Some(OperationStage::FetchManifest);
                 t.progress = TaskProgress::Indeterminable;
@@ -68,9 +155,9 @@ impl<'a> Instance {
             .unwrap();
 
         // Get Minecraft version manifest - Stage 1
-        // TODO: Find already downloaded manifest and redownload
-        // it if outdated
-        let version_manifest = match download_manifest(&client_data.url, Some(paths.meta())).await {
+        // The saved manifest is used when the network is unavailable
+        let offline = client_data.offline;
+        let version_manifest = match get_manifest(&client_data.url, paths.meta(), offline).await {
             Ok((data, path_to_manifest)) => {
                 // Append path
                 if let Some(path) = path_to_manifest {
@@ -90,7 +177,7 @@ impl<'a> Instance {
                 data
             }
             Err(e) => {
-                return Err(InstanceError::CreationFailed(format!(
+                return Err(InstanceError::ManifestFetchFailed(format!(
                     "Failed to download version manifest: {}",
                     e
                 )))
@@ -100,12 +187,12 @@ impl<'a> Instance {
         let version_id = match version_manifest["id"].as_str() {
             Some(ver) => {
                 launch_builder.add_version(&ver);
-                ver
+                ver.to_string()
             }
             None => match version_manifest["version"].as_str() {
                 Some(ver) => {
                     launch_builder.set_arg_value(ArgType::Version, ver);
-                    ver
+                    ver.to_string()
                 }
                 None => {
                     return Err(InstanceError::VersionNotAvailable);
@@ -113,8 +200,94 @@ impl<'a> Instance {
             },
         };
 
+        // Loader libs and main class are merged into the version manifest,
+        // so the following stages handle them like the game ones
+        let mut version_manifest = version_manifest;
+        let loader_profile = if client_data.loader != Loader::Vanilla {
+            ws_status
+                .clone()
+                .start_stage_indeterminable(OperationStage::InstallLoader)
+                .await;
+            global_app_state
+                .update_task(task_id, |t| {
+                    t.stage = Some(OperationStage::InstallLoader);
+                    t.progress = TaskProgress::Indeterminable;
+                })
+                .await
+                .unwrap();
+
+            // Installers of the loaders are downloaded with progress
+            let (progress_sender, progress_receiver) = unbounded();
+            let (loader_result, _) = futures::join!(
+                async {
+                    // Forwarding ends once the sender is dropped
+                    let progress_sender = progress_sender;
+                    resolve_loader(
+                        client_data.loader,
+                        &version_id,
+                        client_data.loader_version.as_deref(),
+                        &paths,
+                        offline,
+                        Some(&progress_sender),
+                    )
+                    .await
+                },
+                forward_download_progress(
+                    ws_status.clone(),
+                    OperationStage::InstallLoader,
+                    progress_receiver
+                )
+            );
+            let profile = loader_result?;
+
+            ws_status
+                .clone()
+                .complete_stage(StageStatus::Completed, OperationStage::InstallLoader, 0.0, None)
+                .await;
+
+            profile
+        } else {
+            None
+        };
+        if let Some(profile) = &loader_profile {
+            profile.merge_into(&mut version_manifest);
+        }
+
+        // Get version assets manifest
+        let assets_manifest_location = paths.assets().join("indexes");
+        let assets_manifest = match get_assets_manifest(
+            &version_manifest,
+            &assets_manifest_location,
+            offline,
+        )
+        .await
+        {
+            Ok((asset_manifest, asset_index)) => {
+                launch_builder.set_arg_value(ArgType::AssetIndex, asset_index);
+                asset_manifest
+            }
+            Err(e) => return Err(InstanceError::ManifestFetchFailed(e)),
+        };
+
+        if verify {
+            Self::verify_files(
+                &version_manifest,
+                &assets_manifest,
+                &paths,
+                Arc::clone(&ws_status),
+                req,
+                task_id,
+                cancel,
+            )
+            .await?;
+        }
+
+        if cancel.is_cancelled() {
+            return Err(InstanceError::Cancelled);
+        }
+
         global_app_state
-            .update_task(task_handle.id, |t| {
+            .update_task(task_id, |t| {
                 t.stage = Some(OperationStage::DownloadLibs);
                 t.progress = TaskProgress::Indeterminable;
             })
@@ -128,6 +301,8 @@ impl<'a> Instance {
             Arc::clone(&ws_status),
             &global_app_state.static_data.db,
             download::libs::ManifestType::Prism,
+            offline,
+            cancel,
         )
         .await
         {
@@ -136,35 +311,20 @@ impl<'a> Instance {
                 launch_builder.add_cps(LibsData::get_classpaths_mut(&mut result));
                 launch_builder.add_natives(LibsData::take_natives_paths(result));
             }
-            Err(e) => {
-                return Err(InstanceError::CreationFailed(format!(
-                    "Failed to download and register libs: {e}"
-                )))
-            }
+            Err(_) if cancel.is_cancelled() => return Err(InstanceError::Cancelled),
+            Err(e) => return Err(e.into()),
         };
 
-        // Get version assets manifest
-        let assets_manifest_location = paths.assets().join("indexes");
-        let assets_manifest = match get_assets_manifest(
-            &version_manifest,
-            &assets_manifest_location.to_str().unwrap(),
-        )
-        .await
-        {
-            Ok((asset_manifest, asset_index)) => {
-                launch_builder.set_arg_value(ArgType::AssetIndex, asset_index);
-                asset_manifest
-            }
-            Err(e) => {
-                return Err(InstanceError::CreationFailed(format!(
-                    "Failed to download assets manifest: {}",
-                    e
-                )))
+        // Libraries bundled with the loader installer aren't downloaded
+        if let Some(profile) = &loader_profile {
+            for lib in &profile.local_libraries {
+                let path = paths.libs().join(lib);
+                launch_builder.add_cp(format!("{}{}", CLASSPATH_SEPARATOR, path.display()));
             }
-        };
+        }
 
         global_app_state
-            .update_task(task_handle.id, |t| {
+            .update_task(task_id, |t| {
                 t.stage = Some(OperationStage::DownloadAssets);
                 t.progress = TaskProgress::Indeterminable;
             })
@@ -173,21 +333,149 @@ impl<'a> Instance {
 
         // Sync & download all assets needed by this version - Stage 3
         let assets_objects_dir = paths.assets().join("objects");
-        AssetsData::sync_assets(
+        match AssetsData::sync_assets(
             &assets_manifest,
             &assets_objects_dir,
             Arc::clone(&ws_status),
-            &global_app_state.static_data.db
+            &global_app_state.static_data.db,
+            offline,
+            cancel,
         )
-        .await;
+        .await
+        {
+            Ok(_) => (),
+            Err(AssetSyncError::Cancelled) => return Err(InstanceError::Cancelled),
+            Err(e) => return Err(e.into()),
+        };
+
+        if cancel.is_cancelled() {
+            return Err(InstanceError::Cancelled);
+        }
+
+        ws_status
+            .clone()
+            .start_stage_indeterminable(OperationStage::InstallJava)
+            .await;
+        global_app_state
+            .update_task(task_id, |t| {
+                t.stage = Some(OperationStage::InstallJava);
+                t.progress = TaskProgress::Indeterminable;
+            })
+            .await
+            .unwrap();
+
+        // Install Java runtime required by this version - Stage 4
+        let requirement = JavaRequirement::from_manifest(&version_manifest);
+        let (progress_sender, progress_receiver) = unbounded();
+        let java = Java::new(
+            requirement.major_version,
+            requirement.component.clone(),
+            paths.java().join(&requirement.component).display().to_string(),
+        )
+        .with_progress(progress_sender)
+        .with_offline(offline);
+        let (java_result, _) = futures::join!(
+            java.init(&global_app_state.static_data.db, cancel),
+            forward_download_progress(
+                ws_status.clone(),
+                OperationStage::InstallJava,
+                progress_receiver
+            )
+        );
+        let mut java_exec = None;
+        let java_status = match java_result {
+            Ok(exec_path) => {
+                launch_builder.set_java_path(&exec_path);
+                java_exec = Some(exec_path);
+                StageStatus::Completed
+            }
+            Err(_) if cancel.is_cancelled() => return Err(InstanceError::Cancelled),
+            Err(e) => {
+                // The game is still launched with java from PATH
+                eprintln!("Failed to install Java runtime {}: {e}", requirement.component);
+                StageStatus::Failed
+            }
+        };
+        ws_status
+            .clone()
+            .complete_stage(java_status, OperationStage::InstallJava, 0.0, None)
+            .await;
+
+        // Processors of the loader installer patch the game - Stage 5
+        if let Some(installer) = loader_profile.as_ref().and_then(|p| p.installer.as_ref()) {
+            ws_status
+                .clone()
+                .start_stage_determinable(
+                    OperationStage::RunProcessors,
+                    None,
+                    0,
+                    installer.processors.len(),
+                )
+                .await;
+            global_app_state
+                .update_task(task_id, |t| {
+                    t.stage = Some(OperationStage::RunProcessors);
+                    t.progress = TaskProgress::Indeterminable;
+                })
+                .await
+                .unwrap();
+
+            // Processors are run only with the runtime required by the game,
+            // Java from PATH may be too old for them
+            let java_exec = match java_exec.as_deref() {
+                Some(java_exec) => java_exec,
+                None => {
+                    return Err(LoaderError::JavaNotAvailable(requirement.component.clone()).into())
+                }
+            };
+
+            let client_jar = paths
+                .libs()
+                .join("com/mojang/minecraft")
+                .join(format!("{}-client.jar", version_id));
+            let (progress_sender, progress_receiver) = unbounded();
+            let (processors_result, _) = futures::join!(
+                async {
+                    let progress_sender = progress_sender;
+                    let context = ProcessorsContext {
+                        libs_dir: paths.libs(),
+                        client_jar: &client_jar,
+                        game_version: &version_id,
+                        java: java_exec,
+                        offline,
+                        progress: Some(&progress_sender),
+                        cancel,
+                    };
+                    installer.run_processors(&context, ws_status.clone()).await
+                },
+                forward_download_progress(
+                    ws_status.clone(),
+                    OperationStage::RunProcessors,
+                    progress_receiver
+                )
+            );
+            match processors_result {
+                Ok(_) => (),
+                Err(LoaderError::Cancelled) => return Err(InstanceError::Cancelled),
+                Err(_) if cancel.is_cancelled() => return Err(InstanceError::Cancelled),
+                Err(e) => return Err(e.into()),
+            };
+
+            ws_status
+                .clone()
+                .complete_stage(StageStatus::Completed, OperationStage::RunProcessors, 0.0, None)
+                .await;
+        }
 
         let instance = Instance {
             name: client_data.name,
             url: client_data.url,
             request_id: client_data.request_id,
 
-            version_id: version_id.to_string(),
+            version_id,
             version_manifest,
+            loader: client_data.loader,
+            loader_version: loader_profile.map(|profile| profile.version),
             paths,
         };
 
@@ -205,14 +493,68 @@ impl<'a> Instance {
             };
         }
 
+        return Ok((instance, launch_builder.fill_defauls().build()));
+    }
+
+    /// Files which fail verification are removed from DB
+    /// and downloaded again by the following stages
+    async fn verify_files<'b: 'c, 'c>(
+        version_manifest: &'c serde_json::Value,
+        assets_manifest: &'c serde_json::Value,
+        paths: &'c paths::InstancePaths,
+        ws_status: OperationWsMessageLocked<'c>,
+        req: &'b EndpointRequest<'a>,
+        task_id: usize,
+        cancel: &CancellationToken,
+    ) -> Result<()> {
+        let global_app_state = req.state();
+        let db = &global_app_state.static_data.db;
+
+        ws_status
+            .clone()
+            .start_stage_determinable(OperationStage::VerifyFiles, None, 0, 0)
+            .await;
         global_app_state
-            .update_task(task_handle.id, |t| {
-                t.stage = None;
-                t.status = TaskStatus::Completed;
+            .update_task(task_id, |t| {
+                t.stage = Some(OperationStage::VerifyFiles);
+                t.progress = TaskProgress::Indeterminable;
             })
             .await
             .unwrap();
 
-        return Ok((instance, launch_builder.fill_defauls().build()));
+        match LibsData::verify_libs(
+            version_manifest,
+            paths,
+            Arc::clone(&ws_status),
+            db,
+            download::libs::ManifestType::Prism,
+            cancel,
+        )
+        .await
+        {
+            Ok(_) => (),
+            Err(_) if cancel.is_cancelled() => return Err(InstanceError::Cancelled),
+            Err(e) => return Err(InstanceError::VerificationFailed(e)),
+        };
+
+        match AssetsData::verify_assets(
+            assets_manifest,
+            paths.assets().join("objects"),
+            Arc::clone(&ws_status),
+            db,
+            cancel,
+        )
+        .await
+        {
+            Ok(_) => (),
+            Err(AssetSyncError::Cancelled) => return Err(InstanceError::Cancelled),
+            Err(e) => return Err(InstanceError::VerificationFailed(e.to_string())),
+        };
+
+        ws_status
+            .complete_stage(StageStatus::Completed, OperationStage::VerifyFiles, 0.0, None)
+            .await;
+
+        Ok(())
     }
 }
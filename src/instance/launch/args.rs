@@ -1,5 +1,12 @@
 use strum::EnumIter;
 
+pub const LAUNCHER_NAME: &str = "sonata-launcher";
+
+#[cfg(target_os = "windows")]
+pub const CLASSPATH_SEPARATOR: &str = ";";
+#[cfg(not(target_os = "windows"))]
+pub const CLASSPATH_SEPARATOR: &str = ":";
+
 #[derive(EnumIter, Clone)]
 pub enum ArgType {
     Username,
@@ -14,6 +21,16 @@ pub enum ArgType {
     UserProperties,
     UserType,
     VersionType,
+    ResolutionWidth,
+    ResolutionHeight,
+
+    // JVM args placeholders
+    NativesDirectory,
+    LibraryDirectory,
+    LauncherName,
+    LauncherVersion,
+    Classpath,
+    ClasspathSeparator,
 }
 
 impl<'a> ArgType {
@@ -34,6 +51,14 @@ impl<'a> ArgType {
             ArgType::UserProperties => "user_properties",
             ArgType::UserType => "user_type",
             ArgType::VersionType => "version_type",
+            ArgType::ResolutionWidth => "resolution_width",
+            ArgType::ResolutionHeight => "resolution_height",
+            ArgType::NativesDirectory => "natives_directory",
+            ArgType::LibraryDirectory => "library_directory",
+            ArgType::LauncherName => "launcher_name",
+            ArgType::LauncherVersion => "launcher_version",
+            ArgType::Classpath => "classpath",
+            ArgType::ClasspathSeparator => "classpath_separator",
         })
     }
 
@@ -43,9 +68,10 @@ impl<'a> ArgType {
 
     /// Retrieve a key of the value in terms of manifest
     /// e.g. "--username" - key | "${auth_player_name}" - placeholder value.
-    /// So it retrieves `--username` by `ArgType::Username`
-    pub fn get_manifest_key(self) -> String {
-        Self::format_key(match self {
+    /// So it retrieves `--username` by `ArgType::Username`.
+    /// JVM args placeholders are not bound to a key, so `None` is returned for them
+    pub fn get_manifest_key(self) -> Option<String> {
+        let key = match self {
             ArgType::Username => "username",
             ArgType::Version => "version",
             ArgType::GameDir => "gameDir",
@@ -58,7 +84,17 @@ impl<'a> ArgType {
             ArgType::UserProperties => "userProperties",
             ArgType::UserType => "userType",
             ArgType::VersionType => "versionType",
-        })
+            ArgType::ResolutionWidth => "width",
+            ArgType::ResolutionHeight => "height",
+            ArgType::NativesDirectory
+            | ArgType::LibraryDirectory
+            | ArgType::LauncherName
+            | ArgType::LauncherVersion
+            | ArgType::Classpath
+            | ArgType::ClasspathSeparator => return None,
+        };
+
+        Some(Self::format_key(key))
     }
 
     fn format_key(key: &'a str) -> String {
@@ -79,6 +115,14 @@ impl<'a> ArgType {
             ArgType::UserProperties => "{}",
             ArgType::UserType => "legacy",
             ArgType::VersionType => " ",
+            ArgType::ResolutionWidth => "854",
+            ArgType::ResolutionHeight => "480",
+            ArgType::NativesDirectory => "natives",
+            ArgType::LibraryDirectory => "libraries",
+            ArgType::LauncherName => LAUNCHER_NAME,
+            ArgType::LauncherVersion => env!("CARGO_PKG_VERSION"),
+            ArgType::Classpath => " ",
+            ArgType::ClasspathSeparator => CLASSPATH_SEPARATOR,
         };
 
         value.to_string()
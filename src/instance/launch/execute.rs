@@ -1,38 +1,109 @@
-use std::{collections::HashMap, path::PathBuf};
-
-use async_std::process::Command;
-
-use crate::instance::launch::{natives::Natives, traits::StartupTraits};
+use std::collections::HashMap;
+
+use crate::{
+    data::GlobalDataState,
+    instance::{
+        launch::{
+            args::ArgType,
+            natives::Natives,
+            supervisor::{Supervisor, SupervisorError},
+            traits::StartupTraits,
+        },
+        options::pages::settings::{JavaSelection, Settings},
+    },
+    java::{arch::manifest_osarch, runtime::JavaRuntime, system::SystemJava},
+    manifest::rules::{LaunchFeature, RuleContext},
+};
 
 use super::LaunchInfo;
 
-pub async fn launch_instance<'a>(manifest: serde_json::Value, launch_info: LaunchInfo) {
-    let args = define_launch_args(manifest, launch_info).await;
+/// Spawn the game under the supervisor and return its pid
+pub async fn launch_instance(
+    state: &GlobalDataState<'static>,
+    instance_id: i64,
+    manifest: serde_json::Value,
+    launch_info: LaunchInfo,
+    settings: &Settings,
+) -> Result<u32, SupervisorError> {
+    let java = resolve_java_executable(state, settings.java(), &launch_info).await?;
+    let args = define_launch_args(manifest, launch_info, settings).await;
     println!("{:#?}", args);
 
-    // Command execution
-    let output =
-        Command::new("java")
-            .args(args)
-            .output()
-            .await
-            .unwrap();
+    Supervisor::spawn(state, instance_id, &java, args, settings.env_vars()).await
+}
 
-    println!("{:#?}", output);
+/// Retrieve the java executable selected in instance settings
+async fn resolve_java_executable(
+    state: &GlobalDataState<'static>,
+    java: &JavaSelection,
+    launch_info: &LaunchInfo,
+) -> Result<String, SupervisorError> {
+    match java {
+        // Falls back to PATH if the required runtime failed to install
+        JavaSelection::Auto => Ok(launch_info
+            .java_path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| String::from("java"))),
+        JavaSelection::System => Ok(String::from("java")),
+        JavaSelection::Path(path) => Ok(path.display().to_string()),
+        JavaSelection::Managed(runtime) => {
+            let arch = manifest_osarch().ok_or_else(|| SupervisorError::JavaNotFound(runtime.clone()))?;
+
+            JavaRuntime::find(&state.static_data.db, runtime, arch)
+                .await
+                .ok()
+                .flatten()
+                .map(|java| java.exec_path)
+                .ok_or_else(|| SupervisorError::JavaNotFound(runtime.clone()))
+        }
+        JavaSelection::Detected(id) => SystemJava::get(&state.static_data.db, *id)
+            .await
+            .map(|java| java.exec_path)
+            .map_err(|_| SupervisorError::JavaNotFound(format!("system java {}", id))),
+    }
 }
 
-async fn define_launch_args<'a>(manifest: serde_json::Value, info: LaunchInfo) -> Vec<String> {
+async fn define_launch_args(
+    manifest: serde_json::Value,
+    info: LaunchInfo,
+    settings: &Settings,
+) -> Vec<String> {
     let mut tmp_args: Vec<String> = Vec::new();
+    println!("Defining launch args for {}", info.name);
+
+    let mut placeholders = info.game_args;
+    placeholders.insert(ArgType::get_value_placeholder(ArgType::Classpath), info.classpath);
+
+    let mut features = info.features;
+    if let Some(window) = settings.window() {
+        features.insert(LaunchFeature::HasCustomResolution);
+        placeholders.insert(
+            ArgType::get_value_placeholder(ArgType::ResolutionWidth),
+            window.width.to_string(),
+        );
+        placeholders.insert(
+            ArgType::get_value_placeholder(ArgType::ResolutionHeight),
+            window.height.to_string(),
+        );
+    }
+    let rules = RuleContext::current().with_features(&features);
 
-    let mut jvm_args = vec![
-        // "-Xdock:icon=icon.png".to_string(),
-        format!(r#"-Xdock:name="Sonata Launcher: {}""#, info.name),
-        "-Xms512M".to_string(),
-        "-Xmx4096M".to_string(),
-    ];
-    tmp_args.append(&mut jvm_args);
+    if let Some(min_memory) = settings.min_memory() {
+        tmp_args.push(format!("-Xms{}M", min_memory));
+    }
+    if let Some(max_memory) = settings.max_memory() {
+        tmp_args.push(format!("-Xmx{}M", max_memory));
+    }
+
+    // Handle natives
+    if !info.native_libs.is_empty() {
+        if let Err(e) = Natives::extract(info.native_libs, &info.natives_dir).await {
+            eprintln!("Error occured during natives extraction: {}", e);
+        }
+    }
 
-   match StartupTraits::extract(&manifest) {
+    match StartupTraits::extract(&manifest) {
         Ok(traits) => {
             for current_trait in traits {
                 match current_trait {
@@ -45,31 +116,25 @@ async fn define_launch_args<'a>(manifest: serde_json::Value, info: LaunchInfo) -
         Err(e) => println!("{e}")
     };
 
-
-    // TODO: Determine windows version and add that argument only on windows 10
-    #[cfg(target_os = "windows")]
-    tmp_args.push("-Dos.name=Windows 10 -Dos.version=10.0".to_string());
-
-    // Handle natives
-    if !info.native_libs.is_empty() {
-        let native_dir = PathBuf::from("/Users/quartix/.sonata/instances/1.5.2/natives");
-
-        match Natives::extract(info.native_libs, &native_dir).await {
-            Ok(_) => {
-                tmp_args.push("-Djava.library.path=".to_owned() + &native_dir.to_string_lossy());
-            }
-            Err(e) => {
-                eprintln!("Error occured during natives extraction: {}", e);
-            }
+    // Check for modern manifest pattern
+    if let Some(jvm_args) = manifest["arguments"]["jvm"].as_array() {
+        handle_args(jvm_args, &rules, &placeholders, &mut tmp_args);
+    } else {
+        // TODO: Determine windows version and add that argument only on windows 10
+        #[cfg(target_os = "windows")]
+        tmp_args.extend(["-Dos.name=Windows 10".to_string(), "-Dos.version=10.0".to_string()]);
+
+        // Legacy manifests don't describe JVM args, so use the ones
+        // the official launcher passes for them
+        for arg in ["-Djava.library.path=${natives_directory}", "-cp", "${classpath}"] {
+            handle_simple_arg(arg, &placeholders, &mut tmp_args);
         }
     }
-
-    // tmp_args.push("-Djna.tmpdir=".to_owned() + natives_dir);
-    // tmp_args.push("-Dorg.lwjgl.system.SharedLibraryExtractPath=".to_owned() + natives_dir);
-    // tmp_args.push("-Dio.netty.native.workdir=/".to_owned() + natives_dir);
-
-    tmp_args.push("-cp".to_string());
-    tmp_args.push(info.classpath);
+    // JVM args of the mod loader, e.g. the module path of Forge
+    if let Some(loader_args) = manifest["+jvmArgs"].as_array() {
+        handle_args(loader_args, &rules, &placeholders, &mut tmp_args);
+    }
+    tmp_args.extend(settings.jvm_args().iter().cloned());
 
     // Append main class that contains run point
     if let Some(main_class) = info.main_class {
@@ -80,59 +145,59 @@ async fn define_launch_args<'a>(manifest: serde_json::Value, info: LaunchInfo) -
         tmp_args.push(String::from("net.minecraft.launchwrapper.Launch"));
     }
 
-    // for arg in info.game_args {
-    //     tmp_args.push(arg.0);
-    //     tmp_args.push(arg.1);
-    // }
-
-    // tmp_args.push("--accessToken".to_string());
-    // tmp_args.push("".to_string());
-
-    // tmp_args.push("--userProperties".to_string());
-    // tmp_args.push("{}".to_string());
-
-    // tmp_args.push("--username".to_string());
-    // tmp_args.push("Melicta".to_string());
-
-    // tmp_args.push("--userType".to_string());
-    // tmp_args.push("legacy".to_string());
-
     // Check for modern manifest pattern
-    if let Some(arguments) = manifest["arguments"].as_object() {
-        if let Some(game_args) = arguments["game"].as_array() {
-            for arg in game_args {
-
-                // First we have to handle simple args
-                // Iterate other `keys`
-                if let Some(simple_arg) = arg.as_str() {
-                    handle_simple_arg(simple_arg, &info.game_args, &mut tmp_args);
-                } else if let Some(_complex_arg) = arg.as_object() {
-                    // println!("Complex arg: {:#?}", complex_arg);
-                    // println!("Complex args is not implemented yet");
-                }
-            }
-        }
+    if let Some(game_args) = manifest["arguments"]["game"].as_array() {
+        handle_args(game_args, &rules, &placeholders, &mut tmp_args);
     } else if let Some(arguments) = manifest["minecraftArguments"].as_str() {
         println!("Using legacy manifest extraction pattern...");
         let arguments = arguments.split_whitespace();
 
         // Iterate other `keys`
         for arg in arguments {
-            handle_simple_arg(arg, &info.game_args, &mut tmp_args);
+            handle_simple_arg(arg, &placeholders, &mut tmp_args);
+        }
+
+        // Legacy manifests don't describe resolution args
+        if settings.window().is_some() {
+            for arg in ["--width", "${resolution_width}", "--height", "${resolution_height}"] {
+                handle_simple_arg(arg, &placeholders, &mut tmp_args);
+            }
         }
     }
+    tmp_args.extend(settings.game_args().iter().cloned());
 
     return tmp_args;
 }
 
-fn _extract_launch_args<'a>(manifest: serde_json::Value) -> Vec<(&'a str, &'a str)> {
-    if let Some(arguments) = manifest["arguments"]["game"].as_array() {
-        for argument in arguments {
-            println!("{}", argument);
+/// Handles `arguments.game` or `arguments.jvm` array of the modern manifest.
+/// Conditional args are only added when their rules allow them
+fn handle_args(
+    args: &Vec<serde_json::Value>,
+    rules: &RuleContext,
+    defined_map: &HashMap<String, String>,
+    output_array: &mut Vec<String>,
+) {
+    for arg in args {
+        if let Some(simple_arg) = arg.as_str() {
+            handle_simple_arg(simple_arg, defined_map, output_array);
+        } else if let Some(complex_arg) = arg.as_object() {
+            if !rules.allows(complex_arg.get("rules").and_then(|v| v.as_array())) {
+                continue;
+            }
+
+            match complex_arg.get("value") {
+                Some(serde_json::Value::String(value)) => {
+                    handle_simple_arg(value, defined_map, output_array);
+                }
+                Some(serde_json::Value::Array(values)) => {
+                    for value in values.iter().filter_map(|v| v.as_str()) {
+                        handle_simple_arg(value, defined_map, output_array);
+                    }
+                }
+                _ => println!("Skipping argument without value: {}", arg),
+            }
         }
     }
-
-    vec![("asd", "asd")]
 }
 
 fn handle_simple_arg(
@@ -140,12 +205,15 @@ fn handle_simple_arg(
     defined_map: &HashMap<String, String>,
     output_array: &mut Vec<String>,
 ) {
-    if &arg[..2] == "${" {
+    if arg.starts_with("${") && arg.ends_with('}') {
         let default = " ".to_string();
 
         // Extract the value from predefined game args or leave it empty
         let value = defined_map.get(arg).unwrap_or(&default);
         output_array.push(value.to_owned());
+    } else if arg.contains("${") {
+        // Placeholders inside of the arg, e.g. `-Djava.library.path=${natives_directory}`
+        output_array.push(substitute_placeholders(arg, defined_map));
     } else {
         // Push arg from manifest.
         // Do not use predefined args as we want to
@@ -153,3 +221,25 @@ fn handle_simple_arg(
         output_array.push(arg.to_string());
     }
 }
+
+/// Replaces every `${name}` in the arg. Unknown placeholders are replaced with nothing
+fn substitute_placeholders(arg: &str, defined_map: &HashMap<String, String>) -> String {
+    let mut result = String::with_capacity(arg.len());
+    let mut rest = arg;
+
+    while let Some(start) = rest.find("${") {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+
+        result.push_str(&rest[..start]);
+        if let Some(value) = defined_map.get(&rest[start..=end]) {
+            result.push_str(value);
+        }
+        rest = &rest[end + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
@@ -1,15 +1,16 @@
 use serde::Deserialize;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
 };
 use strum::IntoEnumIterator;
 
-use crate::instance::launch::args::ArgType;
+use crate::{instance::launch::args::ArgType, manifest::rules::LaunchFeature};
 
 pub mod args;
 pub mod execute;
 pub mod natives;
+pub mod supervisor;
 pub mod traits;
 
 #[derive(Deserialize, Debug)]
@@ -23,8 +24,11 @@ pub struct ClientOptions {
 pub struct LaunchInfo {
     classpath: String,
     native_libs: Vec<PathBuf>,
+    natives_dir: PathBuf,
     main_class: Option<String>,
     game_args: HashMap<String, String>,
+    features: HashSet<LaunchFeature>,
+    java_path: Option<PathBuf>,
     name: String,
 }
 
@@ -32,8 +36,11 @@ pub struct LaunchInfo {
 pub struct LaunchInfoBuilder {
     classpath: Vec<String>,
     native_libs: Vec<PathBuf>,
+    natives_dir: Option<PathBuf>,
     main_class: Option<String>,
     game_args: HashMap<String, String>,
+    features: HashSet<LaunchFeature>,
+    java_path: Option<PathBuf>,
     name: Option<String>,
 }
 
@@ -97,6 +104,25 @@ impl LaunchInfoBuilder {
         self
     }
 
+    /// Sets the directory native libs are extracted to
+    /// and assigns it to the `${natives_directory}` placeholder
+    pub fn set_natives_dir<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.natives_dir = Some(path.as_ref().to_path_buf());
+        Self::set_arg_value(self, ArgType::NativesDirectory, path)
+    }
+
+    /// Enables the feature checked by `features` rules of the manifest arguments
+    pub fn enable_feature(&mut self, feature: LaunchFeature) -> &mut Self {
+        self.features.insert(feature);
+        self
+    }
+
+    /// Sets the managed Java runtime required by the version
+    pub fn set_java_path<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.java_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
     /// Adds the version to `game_args` and assigns
     /// a value to an additional parameter for launching the game
     pub fn add_version<P>(&mut self, version: P) -> &mut Self
@@ -137,8 +163,11 @@ impl LaunchInfoBuilder {
         LaunchInfo {
             classpath,
             native_libs: self.native_libs,
+            natives_dir: self.natives_dir.unwrap_or_default(),
             main_class: self.main_class,
             game_args: self.game_args,
+            features: self.features,
+            java_path: self.java_path,
             name,
         }
     }
@@ -0,0 +1,320 @@
+use std::{collections::HashMap, io, time::Duration};
+
+use async_std::{
+    future::timeout,
+    io::{prelude::BufReadExt, BufReader, Read},
+    process::{Command, ExitStatus, Stdio},
+    stream::StreamExt,
+    task::{self, JoinHandle},
+};
+use thiserror::Error;
+
+use crate::{
+    data::{
+        process::{GameProcess, Processes},
+        GlobalAppDataError, GlobalDataState,
+    },
+    websocket::messages::{
+        console::{ConsoleEvent, ConsoleStream},
+        task::{Task, TaskProgress, TaskStatus},
+    },
+};
+
+/// How long a game may take to exit after graceful termination
+/// before it is killed
+pub const DEFAULT_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Error, Debug)]
+pub enum SupervisorError {
+    #[error("Instance {0} is already running")]
+    AlreadyRunning(i64),
+
+    #[error("Instance {0} is not running")]
+    NotRunning(i64),
+
+    #[error("Failed to spawn game process: {0}")]
+    SpawnFailed(String),
+
+    #[error("Java runtime {0} is not installed")]
+    JavaNotFound(String),
+
+    #[error("Failed to kill game process: {0}")]
+    KillFailed(String),
+
+    #[error("Failed to register game process: {0}")]
+    RegisterFailed(#[from] GlobalAppDataError),
+}
+
+/// Spawns game processes, keeps them in `GlobalDataState`
+/// and streams their output through the processes notifier
+pub struct Supervisor;
+
+impl Supervisor {
+    /// Spawn the game process and start supervising it.
+    /// Returns the pid of the spawned process
+    pub async fn spawn(
+        state: &GlobalDataState<'static>,
+        instance_id: i64,
+        program: &str,
+        args: Vec<String>,
+        envs: &HashMap<String, String>,
+    ) -> Result<u32, SupervisorError> {
+        // Check and reservation happen under the same lock,
+        // so concurrent runs of the instance can't both spawn the game
+        if !state.data.processes.reserve(instance_id).await {
+            return Err(SupervisorError::AlreadyRunning(instance_id));
+        }
+
+        let task_handle = match state
+            .add_task(Task::new_shared(
+                "Run instance",
+                TaskStatus::Pending,
+                None,
+                TaskProgress::Indeterminable,
+                None,
+            ))
+            .await
+        {
+            Ok(handle) => handle,
+            Err(e) => {
+                state.data.processes.release(instance_id).await;
+                return Err(e.into());
+            }
+        };
+
+        let mut child = match Command::new(program)
+            .args(args)
+            .envs(envs)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                state.data.processes.release(instance_id).await;
+                state
+                    .update_task(task_handle.id, |t| t.status = TaskStatus::Failed)
+                    .await?;
+                return Err(SupervisorError::SpawnFailed(e.to_string()));
+            }
+        };
+
+        let processes = state.data.processes.clone();
+        let stdout = child.stdout.take().map(|out| {
+            Self::pipe_lines(out, ConsoleStream::Stdout, instance_id, processes.clone())
+        });
+        let stderr = child.stderr.take().map(|err| {
+            Self::pipe_lines(err, ConsoleStream::Stderr, instance_id, processes.clone())
+        });
+
+        let task_id = task_handle.id;
+        let (exited_tx, exited_rx) = async_channel::bounded::<()>(1);
+        let process = GameProcess::new_shared(instance_id, child, task_handle, exited_rx);
+        let pid = process.lock().await.pid;
+
+        let registered = match state.add_process(process.clone()).await {
+            Ok(_) => {
+                state
+                    .update_task(task_id, |t| t.status = TaskStatus::Running)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = registered {
+            // The exit monitor isn't started yet, so nothing would free the slot
+            // of the instance, and the game is not left running unsupervised
+            if let Err(e) = process.lock().await.child.kill() {
+                eprintln!("Failed to kill instance {}: {}", instance_id, e);
+            }
+            processes.remove(instance_id).await;
+            if let Err(e) = state
+                .update_task(task_id, |t| t.status = TaskStatus::Failed)
+                .await
+            {
+                eprintln!("{e}");
+            }
+
+            return Err(e.into());
+        }
+
+        let state = state.clone();
+        task::spawn(async move {
+            // Only the future is created under the lock, so the process
+            // stays accessible while we are waiting for it
+            let status = process.lock().await.child.status();
+            let status = status.await;
+
+            // Drain the output before reporting the exit
+            for reader in [stdout, stderr].into_iter().flatten() {
+                reader.await;
+            }
+
+            processes.remove(instance_id).await;
+
+            let (code, signal) = match status {
+                Ok(status) => (status.code(), exit_signal(&status)),
+                Err(e) => {
+                    eprintln!("Failed to wait for instance {}: {}", instance_id, e);
+                    (None, None)
+                }
+            };
+
+            let task_status = if process.lock().await.stopping {
+                TaskStatus::Cancelled
+            } else if code == Some(0) {
+                TaskStatus::Completed
+            } else {
+                TaskStatus::Failed
+            };
+
+            if let Err(e) = state
+                .update_task(task_id, |t| t.status = task_status)
+                .await
+            {
+                eprintln!("{e}");
+            }
+
+            if let Err(e) = processes
+                .notify(ConsoleEvent::Exited {
+                    instance_id,
+                    code,
+                    signal,
+                })
+                .await
+            {
+                eprintln!("{e}");
+            }
+
+            // Wake up everyone who waits for the exit
+            drop(exited_tx);
+        });
+
+        Ok(pid)
+    }
+
+    /// Stop the game process: terminate it gracefully at first
+    /// and kill it if it is still alive after `grace_period`.
+    /// Resolves once the exit is reported
+    pub async fn stop<'a>(
+        state: &GlobalDataState<'a>,
+        instance_id: i64,
+        grace_period: Duration,
+    ) -> Result<(), SupervisorError> {
+        let process = match state.data.processes.get(instance_id).await {
+            Some(process) => process,
+            None => return Err(SupervisorError::NotRunning(instance_id)),
+        };
+
+        let (pid, task_id, exited) = {
+            let mut process = process.lock().await;
+            process.stopping = true;
+            (process.pid, process.task.id, process.exited.clone())
+        };
+
+        state
+            .update_task(task_id, |t| t.status = TaskStatus::CancelledAwaiting)
+            .await?;
+
+        match terminate(pid).await {
+            Ok(_) => {
+                if timeout(grace_period, exited.recv()).await.is_ok() {
+                    return Ok(());
+                }
+
+                println!("Instance {} ignored termination, killing it", instance_id);
+            }
+            Err(e) => eprintln!("Failed to terminate instance {}: {}", instance_id, e),
+        }
+
+        if let Err(e) = process.lock().await.child.kill() {
+            // The process may have exited on its own in the meantime
+            if state.is_instance_running(instance_id).await {
+                return Err(SupervisorError::KillFailed(e.to_string()));
+            }
+        }
+
+        // Error means the channel is closed, which is what we wait for
+        let _ = exited.recv().await;
+
+        Ok(())
+    }
+
+    fn pipe_lines<R>(
+        reader: R,
+        stream: ConsoleStream,
+        instance_id: i64,
+        processes: Processes,
+    ) -> JoinHandle<()>
+    where
+        R: Read + Unpin + Send + 'static,
+    {
+        task::spawn(async move {
+            let mut lines = BufReader::new(reader).lines();
+
+            while let Some(line) = lines.next().await {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(e) => {
+                        eprintln!("Failed to read output of instance {}: {}", instance_id, e);
+                        break;
+                    }
+                };
+
+                if let Err(e) = processes
+                    .notify(ConsoleEvent::Line {
+                        instance_id,
+                        stream: stream.clone(),
+                        line,
+                    })
+                    .await
+                {
+                    eprintln!("{e}");
+                }
+            }
+        })
+    }
+}
+
+/// Ask the process to exit, so the game is able to save its state
+#[cfg(unix)]
+async fn terminate(pid: u32) -> io::Result<()> {
+    let status = Command::new("kill")
+        .arg("-TERM")
+        .arg(pid.to_string())
+        .status()
+        .await?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("kill exited with {}", status)))
+    }
+}
+
+#[cfg(windows)]
+async fn terminate(pid: u32) -> io::Result<()> {
+    let status = Command::new("taskkill")
+        .arg("/PID")
+        .arg(pid.to_string())
+        .status()
+        .await?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("taskkill exited with {}", status)))
+    }
+}
+
+#[cfg(unix)]
+fn exit_signal(status: &ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &ExitStatus) -> Option<i32> {
+    None
+}
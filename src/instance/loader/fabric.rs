@@ -0,0 +1,56 @@
+use super::*;
+
+const LOADER_UID: &str = "net.fabricmc.fabric-loader";
+const INTERMEDIARY_UID: &str = "net.fabricmc.intermediary";
+
+/// Fabric loader with the intermediary mappings of `game_version`
+pub async fn resolve(
+    game_version: &str,
+    loader_version: Option<&str>,
+    meta_dir: &Path,
+    offline: bool,
+) -> Result<LoaderProfile, LoaderError> {
+    let version = component_version(LOADER_UID, loader_version, None, meta_dir, offline).await?;
+
+    let path = profile_path(meta_dir, LOADER_UID, &version, game_version);
+    if let Some(profile) = load_profile(&path).await {
+        return Ok(profile);
+    }
+    if offline {
+        return Err(LoaderError::NotCached(path.display().to_string()));
+    }
+
+    let loader = fetch_component(LOADER_UID, &version, meta_dir).await?;
+    // Intermediary is published under the version of the game it maps
+    let intermediary = fetch_component(INTERMEDIARY_UID, game_version, meta_dir).await?;
+
+    let main_class = match loader["mainClass"].as_str() {
+        Some(main_class) => main_class.to_string(),
+        None => {
+            return Err(LoaderError::InvalidMeta(format!(
+                "{} {} has no main class",
+                LOADER_UID, version
+            )))
+        }
+    };
+
+    let mut libraries = component_libraries(&loader).await?;
+    libraries.extend(component_libraries(&intermediary).await?);
+
+    let profile = LoaderProfile {
+        loader: Loader::Fabric,
+        version,
+        main_class,
+        libraries,
+        local_libraries: Vec::new(),
+        jvm_args: Vec::new(),
+        game_args: Vec::new(),
+        installer: None,
+    };
+
+    if let Err(e) = save_profile(&path, &profile).await {
+        eprintln!("Failed to save loader profile {}: {}", path.display(), e);
+    }
+
+    Ok(profile)
+}
@@ -0,0 +1,307 @@
+use std::{fs::File, io::Read, sync::Arc};
+
+use async_std::task;
+use zip::ZipArchive;
+
+use crate::utils::download::{buffer::BufferPool, file_sha1, Download, Downloadable};
+
+use super::{
+    processors::{InstallerData, Processor},
+    *,
+};
+
+const FORGE_UID: &str = "net.minecraftforge";
+const NEOFORGE_UID: &str = "net.neoforged";
+
+const FORGE_MAVEN_URL: &str = "https://maven.minecraftforge.net/";
+const NEOFORGE_MAVEN_URL: &str = "https://maven.neoforged.net/releases/";
+
+/// Installer jar downloaded to the libs dir
+struct InstallerFile {
+    name: String,
+    hash: String,
+    url: String,
+}
+
+impl Downloadable for InstallerFile {
+    fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    fn get_hash(&self) -> &String {
+        &self.hash
+    }
+
+    fn get_url(&self) -> &String {
+        &self.url
+    }
+}
+
+/// Forge or NeoForge described by `install_profile.json` and `version.json` of
+/// their installer. Processors of the installer are run later with the game's Java
+pub async fn resolve(
+    loader: Loader,
+    game_version: &str,
+    loader_version: Option<&str>,
+    paths: &InstancePaths,
+    offline: bool,
+    progress: Option<&ProgressSender>,
+) -> Result<LoaderProfile, LoaderError> {
+    let uid = match loader {
+        Loader::NeoForge => NEOFORGE_UID,
+        _ => FORGE_UID,
+    };
+
+    // Forge versions are also known with the game version prefix, e.g. `1.20.1-47.2.0`
+    let game_prefix = format!("{}-", game_version);
+    let requested =
+        loader_version.map(|version| version.strip_prefix(&game_prefix).unwrap_or(version));
+    let version =
+        component_version(uid, requested, Some(game_version), paths.meta(), offline).await?;
+
+    let path = profile_path(paths.meta(), uid, &version, game_version);
+    if let Some(profile) = load_profile(&path).await {
+        return Ok(profile);
+    }
+    if offline {
+        return Err(LoaderError::NotCached(path.display().to_string()));
+    }
+
+    let (coord, repository) = installer_coord(loader, game_version, &version);
+    let installer_path = match maven::artifact_path(&coord) {
+        Some(installer_path) => installer_path,
+        None => {
+            return Err(LoaderError::InvalidMeta(format!(
+                "Invalid installer {}",
+                coord
+            )))
+        }
+    };
+    let installer = paths.libs().join(&installer_path);
+    download_installer(
+        &format!("{}{}", repository, installer_path),
+        &installer,
+        progress,
+    )
+    .await?;
+
+    let (install_profile, version_json) = read_installer(&installer, paths.libs()).await?;
+    let profile = installer_profile(
+        loader,
+        version,
+        installer_path,
+        &install_profile,
+        &version_json,
+    )?;
+
+    if let Err(e) = save_profile(&path, &profile).await {
+        eprintln!("Failed to save loader profile {}: {}", path.display(), e);
+    }
+
+    Ok(profile)
+}
+
+/// Maven coordinate of the installer and its repository
+pub(super) fn installer_coord(
+    loader: Loader,
+    game_version: &str,
+    version: &str,
+) -> (String, &'static str) {
+    match loader {
+        // The first NeoForge releases kept the coordinates of Forge
+        Loader::NeoForge if game_version == "1.20.1" => (
+            format!("net.neoforged:forge:{}-{}:installer", game_version, version),
+            NEOFORGE_MAVEN_URL,
+        ),
+        Loader::NeoForge => (
+            format!("net.neoforged:neoforge:{}:installer", version),
+            NEOFORGE_MAVEN_URL,
+        ),
+        _ => (
+            format!(
+                "net.minecraftforge:forge:{}-{}:installer",
+                game_version, version
+            ),
+            FORGE_MAVEN_URL,
+        ),
+    }
+}
+
+/// Downloads the installer unless it is already downloaded
+async fn download_installer(
+    url: &str,
+    path: &Path,
+    progress: Option<&ProgressSender>,
+) -> Result<(), LoaderError> {
+    let sha1 = fetch_sha1(url).await?;
+    if file_sha1(path).await.is_ok_and(|hash| hash == sha1) {
+        return Ok(());
+    }
+
+    let installer = InstallerFile {
+        name: url.rsplit('/').next().unwrap_or(url).to_string(),
+        hash: sha1,
+        url: url.to_string(),
+    };
+    let mut download = Download::new(
+        path.to_path_buf(),
+        installer,
+        Arc::new(BufferPool::new(1, 64 * 1024)),
+    );
+    if let Some(progress) = progress {
+        download = download.with_progress(progress.clone());
+    }
+
+    match download.download_with_checksum().await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(LoaderError::DownloadFailed(url.to_string(), e)),
+    }
+}
+
+/// Reads `install_profile.json` and the version JSON it refers to.
+/// Libraries bundled in the `maven` dir of the installer are extracted to the libs dir
+pub(super) async fn read_installer(
+    installer: &Path,
+    libs_dir: &Path,
+) -> Result<(Value, Value), LoaderError> {
+    let installer = installer.to_path_buf();
+    let libs_dir = libs_dir.to_path_buf();
+    let name = installer.display().to_string();
+
+    task::spawn_blocking(move || {
+        let file = File::open(&installer).map_err(|e| e.to_string())?;
+        let mut archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
+
+        let install_profile = read_json(&mut archive, "install_profile.json")?;
+        // Installers of 1.12.2 and older describe the version inside of the profile
+        if install_profile.get("install").is_some() {
+            return Err("legacy installers are not supported".to_string());
+        }
+
+        let version_name = install_profile["json"].as_str().unwrap_or("/version.json");
+        let version_json = read_json(&mut archive, version_name.trim_start_matches('/'))?;
+
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+            let path = match entry
+                .enclosed_name()
+                .and_then(|p| p.strip_prefix("maven").ok().map(|p| p.to_path_buf()))
+            {
+                Some(path) if entry.is_file() => libs_dir.join(path),
+                _ => continue,
+            };
+
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            let mut out = File::create(&path).map_err(|e| e.to_string())?;
+            std::io::copy(&mut entry, &mut out).map_err(|e| e.to_string())?;
+        }
+
+        Ok((install_profile, version_json))
+    })
+    .await
+    .map_err(|e| LoaderError::InvalidInstaller(name, e))
+}
+
+fn read_json(archive: &mut ZipArchive<File>, name: &str) -> Result<Value, String> {
+    let mut data = String::new();
+    archive
+        .by_name(name)
+        .map_err(|e| format!("{}: {}", name, e))?
+        .read_to_string(&mut data)
+        .map_err(|e| format!("{}: {}", name, e))?;
+
+    serde_json::from_str(&data).map_err(|e| format!("{}: {}", name, e))
+}
+
+/// Profile of the loader from the files of its installer
+pub(super) fn installer_profile(
+    loader: Loader,
+    version: String,
+    installer: String,
+    install_profile: &Value,
+    version_json: &Value,
+) -> Result<LoaderProfile, LoaderError> {
+    let main_class = match version_json["mainClass"].as_str() {
+        Some(main_class) => main_class.to_string(),
+        None => {
+            return Err(LoaderError::InvalidMeta(format!(
+                "{} has no main class",
+                installer
+            )))
+        }
+    };
+
+    // Libraries without URL are bundled with the installer or produced by processors
+    let mut libraries = Vec::new();
+    let mut local_libraries = Vec::new();
+    for lib in version_json["libraries"].as_array().into_iter().flatten() {
+        let artifact = &lib["downloads"]["artifact"];
+        if artifact["url"].as_str().is_some_and(|url| !url.is_empty()) {
+            libraries.push(lib.clone());
+            continue;
+        }
+
+        let path = artifact["path"]
+            .as_str()
+            .map(String::from)
+            .or_else(|| lib["name"].as_str().and_then(maven::artifact_path));
+        match path {
+            Some(path) => local_libraries.push(path),
+            None => {
+                return Err(LoaderError::InvalidMeta(format!(
+                    "Library can't be located: {}",
+                    lib
+                )))
+            }
+        }
+    }
+
+    // Client jar of the launcher is named after the version with the `-client` suffix,
+    // it must be ignored by the module layer of the loader like in the official launcher
+    let jvm_args = string_args(&version_json["arguments"]["jvm"])
+        .into_iter()
+        .map(|arg| arg.replace("${version_name}.jar", "${version_name}-client.jar"))
+        .collect();
+    let game_args = string_args(&version_json["arguments"]["game"]);
+
+    let data = install_profile["data"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| Some((key.clone(), value["client"].as_str()?.to_string())))
+        .collect();
+    let processors: Vec<Processor> =
+        match serde_json::from_value(install_profile["processors"].clone()) {
+            Ok(processors) => processors,
+            Err(e) => {
+                return Err(LoaderError::InvalidMeta(format!(
+                    "Invalid processors: {}",
+                    e
+                )))
+            }
+        };
+
+    Ok(LoaderProfile {
+        loader,
+        version,
+        main_class,
+        libraries,
+        local_libraries,
+        jvm_args,
+        game_args,
+        installer: Some(InstallerData {
+            installer,
+            data,
+            processors: processors
+                .into_iter()
+                .filter(Processor::is_client)
+                .collect(),
+            libraries: install_profile["libraries"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default(),
+        }),
+    })
+}
@@ -0,0 +1,382 @@
+use std::path::{Path, PathBuf};
+
+use async_std::fs;
+use futures::future::try_join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::{
+    instance::{download::manifest::get_manifest, paths::InstancePaths},
+    utils::{
+        download::{download_with_progress, progress::ProgressSender},
+        maven,
+    },
+};
+
+use self::processors::InstallerData;
+
+mod fabric;
+mod forge;
+pub mod processors;
+mod quilt;
+
+#[cfg(test)]
+mod tests;
+
+/// Used by Prism meta when the library doesn't set its repository
+const DEFAULT_MAVEN_URL: &str = "https://libraries.minecraft.net/";
+
+/// Servers describing the loaders
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoaderMetaProviders {
+    /// Prism meta describes loaders as components in the same format as Minecraft
+    Prism,
+    /// Quilt meta serves launcher profiles in the Fabric format
+    Quilt,
+}
+
+impl LoaderMetaProviders {
+    pub fn base_url(&self) -> &'static str {
+        match self {
+            LoaderMetaProviders::Prism => "https://meta.prismlauncher.org/v1/",
+            LoaderMetaProviders::Quilt => "https://meta.quiltmc.org/v3/",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Loader {
+    #[default]
+    Vanilla,
+    Fabric,
+    Forge,
+    NeoForge,
+    Quilt,
+}
+
+impl Loader {
+    /// Name stored in the `loader` column of the instance
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Loader::Vanilla => "vanilla",
+            Loader::Fabric => "fabric",
+            Loader::Forge => "forge",
+            Loader::NeoForge => "neoforge",
+            Loader::Quilt => "quilt",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "vanilla" => Some(Loader::Vanilla),
+            "fabric" => Some(Loader::Fabric),
+            "forge" => Some(Loader::Forge),
+            "neoforge" => Some(Loader::NeoForge),
+            "quilt" => Some(Loader::Quilt),
+            _ => None,
+        }
+    }
+
+    /// Loaders installed by running the processors of their installer
+    pub fn has_installer(&self) -> bool {
+        matches!(self, Loader::Forge | Loader::NeoForge)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum LoaderError {
+    #[error("Loader version is not available: {0}")]
+    VersionNotFound(String),
+
+    #[error("Failed to fetch loader metadata: {0}")]
+    MetaFetchFailed(String),
+
+    #[error("Loader metadata is invalid: {0}")]
+    InvalidMeta(String),
+
+    #[error("Failed to fetch checksum of {0}: {1}")]
+    ChecksumFetchFailed(String, String),
+
+    #[error("Loader profile {0} is not resolved yet and can't be fetched offline")]
+    NotCached(String),
+
+    #[error("Failed to download {0}: {1}")]
+    DownloadFailed(String, String),
+
+    #[error("Failed to read installer {0}: {1}")]
+    InvalidInstaller(String, String),
+
+    #[error("Processor {0} failed: {1}")]
+    ProcessorFailed(String, String),
+
+    #[error("Java runtime {0} required by the installer processors is not installed")]
+    JavaNotAvailable(String),
+
+    #[error("Loader installation was cancelled")]
+    Cancelled,
+}
+
+/// Libraries and main class the loader puts on top of the Minecraft manifest
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LoaderProfile {
+    pub loader: Loader,
+    pub version: String,
+    pub main_class: String,
+    /// Libraries in the Prism meta format with `downloads.artifact` filled
+    pub libraries: Vec<Value>,
+    /// Libraries bundled with the installer or produced by its processors.
+    /// Paths are relative to the libs dir
+    #[serde(default)]
+    pub local_libraries: Vec<String>,
+    #[serde(default)]
+    pub jvm_args: Vec<String>,
+    #[serde(default)]
+    pub game_args: Vec<String>,
+    /// Processors which patch the game before the first launch
+    #[serde(default)]
+    pub installer: Option<InstallerData>,
+}
+
+impl LoaderProfile {
+    /// Loader libs go first in the classpath. Game libs which
+    /// the loader ships in another version are removed
+    pub fn merge_into(&self, manifest: &mut Value) {
+        let loader_keys: Vec<String> = self.libraries.iter().filter_map(library_key).collect();
+
+        let mut libraries = self.libraries.clone();
+        if let Some(game_libs) = manifest["libraries"].as_array() {
+            libraries.extend(
+                game_libs
+                    .iter()
+                    .filter(|lib| !library_key(lib).is_some_and(|key| loader_keys.contains(&key)))
+                    .cloned(),
+            );
+        }
+
+        manifest["libraries"] = Value::Array(libraries);
+        manifest["mainClass"] = Value::String(self.main_class.clone());
+
+        // Prism meta puts JVM args of the components there
+        if !self.jvm_args.is_empty() {
+            let mut jvm_args = manifest["+jvmArgs"].as_array().cloned().unwrap_or_default();
+            jvm_args.extend(self.jvm_args.iter().cloned().map(Value::String));
+            manifest["+jvmArgs"] = Value::Array(jvm_args);
+        }
+
+        if !self.game_args.is_empty() {
+            if let Some(game_args) = manifest["arguments"]["game"].as_array_mut() {
+                game_args.extend(self.game_args.iter().cloned().map(Value::String));
+            } else {
+                let mut arguments: Vec<&str> = manifest["minecraftArguments"]
+                    .as_str()
+                    .map(|args| args.split_whitespace().collect())
+                    .unwrap_or_default();
+                arguments.extend(self.game_args.iter().map(String::as_str));
+                manifest["minecraftArguments"] = Value::String(arguments.join(" "));
+            }
+        }
+    }
+}
+
+/// Resolves the profile of `loader` for `game_version`. The latest recommended
+/// loader version is used when `loader_version` is not set, vanilla has no profile.
+/// Installers of the loaders are downloaded to the libs dir
+pub async fn resolve_loader(
+    loader: Loader,
+    game_version: &str,
+    loader_version: Option<&str>,
+    paths: &InstancePaths,
+    offline: bool,
+    progress: Option<&ProgressSender>,
+) -> Result<Option<LoaderProfile>, LoaderError> {
+    let meta_dir = paths.meta();
+
+    match loader {
+        Loader::Vanilla => Ok(None),
+        Loader::Fabric => fabric::resolve(game_version, loader_version, meta_dir, offline)
+            .await
+            .map(Some),
+        Loader::Quilt => quilt::resolve(game_version, loader_version, meta_dir, offline)
+            .await
+            .map(Some),
+        Loader::Forge | Loader::NeoForge => {
+            forge::resolve(loader, game_version, loader_version, paths, offline, progress)
+                .await
+                .map(Some)
+        }
+    }
+}
+
+/// Requested version of the component or the latest recommended one.
+/// Only versions requiring `game_version` are considered when it is set
+async fn component_version(
+    uid: &str,
+    requested: Option<&str>,
+    game_version: Option<&str>,
+    meta_dir: &Path,
+    offline: bool,
+) -> Result<String, LoaderError> {
+    if let Some(version) = requested {
+        return Ok(version.to_string());
+    }
+
+    let url = format!("{}{}/index.json", LoaderMetaProviders::Prism.base_url(), uid);
+    let index = match get_manifest(&url, meta_dir.join(uid), offline).await {
+        Ok((index, _)) => index,
+        Err(e) => return Err(LoaderError::MetaFetchFailed(e.to_string())),
+    };
+
+    latest_version(&index, game_version).ok_or_else(|| match game_version {
+        Some(game_version) => {
+            LoaderError::VersionNotFound(format!("{} has no versions for {}", uid, game_version))
+        }
+        None => LoaderError::VersionNotFound(format!("{} has no versions", uid)),
+    })
+}
+
+/// Index of Prism meta lists versions from the newest one
+fn latest_version(index: &Value, game_version: Option<&str>) -> Option<String> {
+    let versions: Vec<&Value> = index["versions"]
+        .as_array()?
+        .iter()
+        .filter(|version| game_version.is_none_or(|game| requires_game(version, game)))
+        .collect();
+
+    versions
+        .iter()
+        .find(|version| version["recommended"].as_bool() == Some(true))
+        .or_else(|| versions.first())
+        .and_then(|version| version["version"].as_str())
+        .map(String::from)
+}
+
+fn requires_game(version: &Value, game_version: &str) -> bool {
+    version["requires"].as_array().is_some_and(|requires| {
+        requires
+            .iter()
+            .any(|req| req["uid"] == "net.minecraft" && req["equals"] == game_version)
+    })
+}
+
+async fn fetch_component(uid: &str, version: &str, meta_dir: &Path) -> Result<Value, LoaderError> {
+    let url = format!("{}{}/{}.json", LoaderMetaProviders::Prism.base_url(), uid, version);
+
+    match get_manifest(&url, meta_dir.join(uid), false).await {
+        Ok((component, _)) => Ok(component),
+        Err(e) => Err(LoaderError::MetaFetchFailed(format!(
+            "{} {}: {}",
+            uid, version, e
+        ))),
+    }
+}
+
+/// Libraries of the component with their checksums. Loader components
+/// only name the Maven repository, so checksums are fetched from it
+async fn component_libraries(component: &Value) -> Result<Vec<Value>, LoaderError> {
+    let libs = match component["libraries"].as_array() {
+        Some(libs) => libs,
+        None => return Ok(Vec::new()),
+    };
+
+    try_join_all(libs.iter().map(|lib| async move {
+        if lib["downloads"]["artifact"]["sha1"].is_string() {
+            return Ok(lib.clone());
+        }
+
+        let (name, (path, url)) = match (lib["name"].as_str(), library_url(lib)) {
+            (Some(name), Some(location)) => (name, location),
+            _ => {
+                return Err(LoaderError::InvalidMeta(format!(
+                    "Library can't be located: {}",
+                    lib
+                )))
+            }
+        };
+        let sha1 = fetch_sha1(&url).await?;
+
+        Ok(json!({
+            "name": name,
+            "downloads": {
+                "artifact": {
+                    "path": path,
+                    "url": url,
+                    "sha1": sha1,
+                }
+            }
+        }))
+    }))
+    .await
+}
+
+/// Path of the library jar and its URL in the Maven repository
+fn library_url(lib: &Value) -> Option<(String, String)> {
+    let path = maven::artifact_path(lib["name"].as_str()?)?;
+    let repository = lib["url"].as_str().unwrap_or(DEFAULT_MAVEN_URL);
+    let url = format!("{}/{}", repository.trim_end_matches('/'), path);
+
+    Some((path, url))
+}
+
+/// Maven repositories publish the checksum of every file next to it
+async fn fetch_sha1(url: &str) -> Result<String, LoaderError> {
+    let sha1_url = format!("{}.sha1", url);
+    let data = match download_with_progress(&sha1_url, &sha1_url, None, None).await {
+        Ok(data) => data,
+        Err(e) => return Err(LoaderError::ChecksumFetchFailed(url.to_string(), e)),
+    };
+
+    // Some repositories append the file name after the checksum
+    let text = String::from_utf8_lossy(&data);
+    match text.split_whitespace().next() {
+        Some(sha1) if sha1.len() == 40 && sha1.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Ok(sha1.to_lowercase())
+        }
+        _ => Err(LoaderError::ChecksumFetchFailed(
+            url.to_string(),
+            format!("unexpected checksum {:?}", text.trim()),
+        )),
+    }
+}
+
+/// String entries of `arguments.jvm` or `arguments.game`, conditional ones are skipped
+fn string_args(args: &Value) -> Vec<String> {
+    args.as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|arg| arg.as_str())
+        .map(String::from)
+        .collect()
+}
+
+/// Name of the library without its version, e.g. `org.ow2.asm:asm`
+fn library_key(lib: &Value) -> Option<String> {
+    let mut parts: Vec<&str> = lib["name"].as_str()?.split(':').collect();
+    if parts.len() > 2 {
+        parts.remove(2);
+    }
+
+    Some(parts.join(":"))
+}
+
+/// Resolved profiles are saved, so checksums aren't fetched on every launch
+fn profile_path(meta_dir: &Path, uid: &str, version: &str, game_version: &str) -> PathBuf {
+    meta_dir
+        .join(uid)
+        .join("profiles")
+        .join(format!("{}-{}.json", version, game_version))
+}
+
+async fn load_profile(path: &Path) -> Option<LoaderProfile> {
+    let data = fs::read(path).await.ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+async fn save_profile(path: &Path, profile: &LoaderProfile) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).await?;
+    }
+
+    fs::write(path, serde_json::to_vec_pretty(profile)?).await
+}
@@ -0,0 +1,445 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_std::{process::Command, task};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use zip::ZipArchive;
+
+use crate::{
+    instance::{
+        launch::args::CLASSPATH_SEPARATOR,
+        websocket::{OperationWsExt, OperationWsMessageLocked},
+    },
+    utils::{
+        cancel::CancellationToken,
+        download::{
+            buffer::BufferPool, file_sha1, progress::ProgressSender, Download, Downloadable,
+        },
+        maven,
+    },
+    websocket::messages::operation::stage::OperationStage,
+};
+
+use super::LoaderError;
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Processors of the installer with the data their args refer to
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InstallerData {
+    /// Path of the installer jar relative to the libs dir
+    pub installer: String,
+    /// Values of the `{KEY}` tokens for the client side
+    pub data: HashMap<String, String>,
+    /// Client processors in the order they are run
+    pub processors: Vec<Processor>,
+    /// Libraries the processors are run with, in the Mojang format
+    pub libraries: Vec<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Processor {
+    /// Maven coordinate of the processor jar
+    pub jar: String,
+    #[serde(default)]
+    pub classpath: Vec<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Files produced by the processor and their checksums
+    #[serde(default)]
+    pub outputs: HashMap<String, String>,
+    /// Processors without sides are run for both of them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sides: Option<Vec<String>>,
+}
+
+/// Directories and tools the processors are run with
+pub struct ProcessorsContext<'a> {
+    pub libs_dir: &'a Path,
+    /// Vanilla client jar patched by the processors
+    pub client_jar: &'a Path,
+    pub game_version: &'a str,
+    /// Java executable of the runtime required by the game
+    pub java: &'a str,
+    pub offline: bool,
+    pub progress: Option<&'a ProgressSender>,
+    pub cancel: &'a CancellationToken,
+}
+
+/// Library of the processors downloaded before they are run
+struct ProcessorLibrary {
+    name: String,
+    hash: String,
+    url: String,
+}
+
+impl Downloadable for ProcessorLibrary {
+    fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    fn get_hash(&self) -> &String {
+        &self.hash
+    }
+
+    fn get_url(&self) -> &String {
+        &self.url
+    }
+}
+
+impl Processor {
+    pub fn is_client(&self) -> bool {
+        self.sides
+            .as_ref()
+            .is_none_or(|sides| sides.iter().any(|side| side == "client"))
+    }
+}
+
+impl InstallerData {
+    /// Runs the processors whose outputs are missing or don't match their checksums.
+    /// Successful installation is marked next to the installer,
+    /// so processors without outputs aren't run on every launch
+    pub async fn run_processors<'a>(
+        &self,
+        context: &ProcessorsContext<'_>,
+        ws_status: OperationWsMessageLocked<'a>,
+    ) -> Result<(), LoaderError> {
+        let installer = context.libs_dir.join(&self.installer);
+        let marker = installer.with_extension("installed");
+        let extract_dir = installer.with_extension("");
+        let data = self.resolve_data(context, &installer, &extract_dir);
+
+        let mut outdated = Vec::new();
+        for processor in &self.processors {
+            if !outputs_match(processor, &data).await? {
+                outdated.push(processor);
+            }
+        }
+        if outdated.is_empty() && async_std::path::Path::new(&marker).exists().await {
+            return Ok(());
+        }
+        if context.offline {
+            return Err(LoaderError::NotCached(installer.display().to_string()));
+        }
+
+        self.download_libraries(context).await?;
+        extract_data_files(installer.clone(), extract_dir.clone(), self.data.clone()).await?;
+
+        let total = self.processors.len();
+        let mut ws_status = ws_status;
+        for (i, processor) in self.processors.iter().enumerate() {
+            if context.cancel.is_cancelled() {
+                return Err(LoaderError::Cancelled);
+            }
+
+            // Processors with outputs are skipped like in the official installer
+            let skip = !processor.outputs.is_empty() && !outdated.contains(&processor);
+            if !skip {
+                run_processor(processor, &data, context).await?;
+
+                if !outputs_match(processor, &data).await? {
+                    return Err(LoaderError::ProcessorFailed(
+                        processor.jar.clone(),
+                        "outputs don't match their checksums".to_string(),
+                    ));
+                }
+            }
+
+            ws_status = ws_status
+                .update_determinable(OperationStage::RunProcessors, None, i + 1, total)
+                .await;
+        }
+
+        if let Err(e) = async_std::fs::write(&marker, b"").await {
+            eprintln!("Failed to mark {} as installed: {}", installer.display(), e);
+        }
+        let _ = async_std::fs::remove_dir_all(&extract_dir).await;
+
+        Ok(())
+    }
+
+    /// Data of the installer with the values set by the launcher.
+    /// Files of the installer are referenced in the `extract_dir`
+    fn resolve_data(
+        &self,
+        context: &ProcessorsContext<'_>,
+        installer: &Path,
+        extract_dir: &Path,
+    ) -> HashMap<String, String> {
+        let root = context.libs_dir.parent().unwrap_or(context.libs_dir);
+        let mut data: HashMap<String, String> = self
+            .data
+            .iter()
+            .map(|(key, value)| {
+                let value = match value.strip_prefix('/') {
+                    Some(file) => extract_dir.join(file).display().to_string(),
+                    None => resolve_value(value, context.libs_dir),
+                };
+                (key.clone(), value)
+            })
+            .collect();
+
+        for (key, value) in [
+            ("SIDE", "client".to_string()),
+            ("MINECRAFT_JAR", context.client_jar.display().to_string()),
+            ("MINECRAFT_VERSION", context.game_version.to_string()),
+            ("ROOT", root.display().to_string()),
+            ("INSTALLER", installer.display().to_string()),
+            ("LIBRARY_DIR", context.libs_dir.display().to_string()),
+        ] {
+            data.insert(key.to_string(), value);
+        }
+
+        data
+    }
+
+    /// Libraries bundled with the installer are already extracted
+    async fn download_libraries(&self, context: &ProcessorsContext<'_>) -> Result<(), LoaderError> {
+        let buffers = Arc::new(BufferPool::new(1, BUFFER_SIZE));
+
+        for lib in &self.libraries {
+            let name = lib["name"].as_str().unwrap_or_default();
+            let artifact = &lib["downloads"]["artifact"];
+            let path = match artifact["path"]
+                .as_str()
+                .map(String::from)
+                .or_else(|| maven::artifact_path(name))
+            {
+                Some(path) => context.libs_dir.join(path),
+                None => {
+                    return Err(LoaderError::InvalidMeta(format!(
+                        "Library can't be located: {}",
+                        lib
+                    )))
+                }
+            };
+
+            let (url, sha1) = match (artifact["url"].as_str(), artifact["sha1"].as_str()) {
+                (Some(url), Some(sha1)) if !url.is_empty() => (url, sha1),
+                _ => continue,
+            };
+            if file_sha1(&path).await.is_ok_and(|hash| hash == sha1) {
+                continue;
+            }
+
+            let library = ProcessorLibrary {
+                name: name.to_string(),
+                hash: sha1.to_string(),
+                url: url.to_string(),
+            };
+            let mut download = Download::new(path, library, Arc::clone(&buffers))
+                .with_cancel(context.cancel.clone());
+            if let Some(progress) = context.progress {
+                download = download.with_progress(progress.clone());
+            }
+
+            if let Err(e) = download.download_with_checksum().await {
+                if context.cancel.is_cancelled() {
+                    return Err(LoaderError::Cancelled);
+                }
+                return Err(LoaderError::DownloadFailed(url.to_string(), e));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// `[coord]` is the path of the library, `'literal'` is the literal itself
+fn resolve_value(value: &str, libs_dir: &Path) -> String {
+    if let Some(coord) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        if let Some(path) = maven::artifact_path(coord) {
+            return libs_dir.join(path).display().to_string();
+        }
+    }
+
+    if let Some(literal) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        return literal.to_string();
+    }
+
+    value.to_string()
+}
+
+/// Replaces `{KEY}` tokens of the processor arg with the data values
+pub(super) fn substitute_arg(
+    arg: &str,
+    data: &HashMap<String, String>,
+    libs_dir: &Path,
+) -> Result<String, String> {
+    if arg.starts_with('[') && arg.ends_with(']') {
+        return Ok(resolve_value(arg, libs_dir));
+    }
+
+    let mut result = String::with_capacity(arg.len());
+    let mut rest = arg;
+    while let Some(start) = rest.find('{') {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+
+        let key = &rest[start + 1..end];
+        match data.get(key) {
+            Some(value) => {
+                result.push_str(&rest[..start]);
+                result.push_str(value);
+            }
+            None => return Err(format!("Unknown data key {}", key)),
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+async fn outputs_match(
+    processor: &Processor,
+    data: &HashMap<String, String>,
+) -> Result<bool, LoaderError> {
+    for (path, sha1) in &processor.outputs {
+        let (path, sha1) = match (
+            substitute_arg(path, data, Path::new("")),
+            substitute_arg(sha1, data, Path::new("")),
+        ) {
+            (Ok(path), Ok(sha1)) => (path, sha1),
+            (Err(e), _) | (_, Err(e)) => {
+                return Err(LoaderError::ProcessorFailed(processor.jar.clone(), e))
+            }
+        };
+
+        if !file_sha1(Path::new(&path))
+            .await
+            .is_ok_and(|hash| hash == sha1.to_lowercase())
+        {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+async fn run_processor(
+    processor: &Processor,
+    data: &HashMap<String, String>,
+    context: &ProcessorsContext<'_>,
+) -> Result<(), LoaderError> {
+    let failed = |reason: String| LoaderError::ProcessorFailed(processor.jar.clone(), reason);
+
+    let jar = match maven::artifact_path(&processor.jar) {
+        Some(path) => context.libs_dir.join(path),
+        None => return Err(failed("jar can't be located".to_string())),
+    };
+    let main_class = jar_main_class(jar.clone()).await.map_err(failed)?;
+
+    let mut classpath = vec![jar.display().to_string()];
+    for coord in &processor.classpath {
+        match maven::artifact_path(coord) {
+            Some(path) => classpath.push(context.libs_dir.join(path).display().to_string()),
+            None => {
+                return Err(failed(format!(
+                    "classpath entry {} can't be located",
+                    coord
+                )))
+            }
+        }
+    }
+
+    let args = processor
+        .args
+        .iter()
+        .map(|arg| substitute_arg(arg, data, context.libs_dir))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(failed)?;
+
+    let output = Command::new(context.java)
+        .arg("-cp")
+        .arg(classpath.join(CLASSPATH_SEPARATOR))
+        .arg(main_class)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| failed(e.to_string()))?;
+
+    if !output.status.success() {
+        // The end of the output usually contains the exception
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+        let reason = tail.into_iter().rev().collect::<Vec<_>>().join("\n");
+
+        return Err(failed(format!("{}: {}", output.status, reason)));
+    }
+
+    Ok(())
+}
+
+/// `Main-Class` from the manifest of the jar
+async fn jar_main_class(jar: PathBuf) -> Result<String, String> {
+    task::spawn_blocking(move || {
+        let file = File::open(&jar).map_err(|e| format!("{}: {}", jar.display(), e))?;
+        let mut archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
+
+        let mut manifest = String::new();
+        archive
+            .by_name("META-INF/MANIFEST.MF")
+            .map_err(|e| e.to_string())?
+            .read_to_string(&mut manifest)
+            .map_err(|e| e.to_string())?;
+
+        // Long values are continued on the lines starting with a space
+        let manifest = manifest.replace("\r\n", "\n").replace("\n ", "");
+        manifest
+            .lines()
+            .find_map(|line| line.strip_prefix("Main-Class:"))
+            .map(|class| class.trim().to_string())
+            .ok_or_else(|| format!("{} has no main class", jar.display()))
+    })
+    .await
+}
+
+/// Extracts files of the installer referenced by the data, e.g. `/data/client.lzma`
+async fn extract_data_files(
+    installer: PathBuf,
+    extract_dir: PathBuf,
+    data: HashMap<String, String>,
+) -> Result<(), LoaderError> {
+    let invalid = |e: String| LoaderError::InvalidInstaller(installer.display().to_string(), e);
+
+    let files: Vec<String> = data
+        .values()
+        .filter_map(|value| value.strip_prefix('/'))
+        .map(String::from)
+        .collect();
+    if files.is_empty() {
+        return Ok(());
+    }
+
+    let installer_path = installer.clone();
+    task::spawn_blocking(move || {
+        let file = File::open(&installer_path).map_err(|e| e.to_string())?;
+        let mut archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
+
+        for name in files {
+            let mut entry = archive
+                .by_name(&name)
+                .map_err(|e| format!("{}: {}", name, e))?;
+            let path = extract_dir.join(&name);
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+
+            let mut out = File::create(&path).map_err(|e| e.to_string())?;
+            std::io::copy(&mut entry, &mut out).map_err(|e| e.to_string())?;
+        }
+
+        Ok(())
+    })
+    .await
+    .map_err(invalid)
+}
@@ -0,0 +1,121 @@
+use crate::utils::download::download_in_json;
+
+use super::*;
+
+const LOADER_UID: &str = "org.quiltmc.quilt-loader";
+
+/// Quilt loader with the hashed and intermediary mappings of `game_version`
+pub async fn resolve(
+    game_version: &str,
+    loader_version: Option<&str>,
+    meta_dir: &Path,
+    offline: bool,
+) -> Result<LoaderProfile, LoaderError> {
+    let version = match loader_version {
+        Some(version) => version.to_string(),
+        None => loader_version_for(game_version, meta_dir, offline).await?,
+    };
+
+    let path = profile_path(meta_dir, LOADER_UID, &version, game_version);
+    if let Some(profile) = load_profile(&path).await {
+        return Ok(profile);
+    }
+    if offline {
+        return Err(LoaderError::NotCached(path.display().to_string()));
+    }
+
+    // Launcher profile already lists the mappings next to the loader libs
+    let url = format!(
+        "{}versions/loader/{}/{}/profile/json",
+        LoaderMetaProviders::Quilt.base_url(),
+        game_version,
+        version
+    );
+    let launcher_profile = match download_in_json(&url).await {
+        Ok(launcher_profile) => launcher_profile,
+        Err(e) => {
+            return Err(LoaderError::MetaFetchFailed(format!(
+                "{} {}: {}",
+                LOADER_UID, version, e
+            )))
+        }
+    };
+
+    let profile = loader_profile(launcher_profile, version).await?;
+
+    if let Err(e) = save_profile(&path, &profile).await {
+        eprintln!("Failed to save loader profile {}: {}", path.display(), e);
+    }
+
+    Ok(profile)
+}
+
+/// Latest stable loader supporting `game_version`
+async fn loader_version_for(
+    game_version: &str,
+    meta_dir: &Path,
+    offline: bool,
+) -> Result<String, LoaderError> {
+    let url = format!(
+        "{}versions/loader/{}",
+        LoaderMetaProviders::Quilt.base_url(),
+        game_version
+    );
+    let versions = match get_manifest(&url, meta_dir.join(LOADER_UID), offline).await {
+        Ok((versions, _)) => versions,
+        Err(e) => return Err(LoaderError::MetaFetchFailed(e.to_string())),
+    };
+
+    latest_loader_version(&versions).ok_or_else(|| {
+        LoaderError::VersionNotFound(format!(
+            "{} has no versions for {}",
+            LOADER_UID, game_version
+        ))
+    })
+}
+
+/// Quilt meta lists loaders from the newest one, betas have a pre-release suffix
+pub(super) fn latest_loader_version(versions: &Value) -> Option<String> {
+    let versions: Vec<&str> = versions
+        .as_array()?
+        .iter()
+        .filter_map(|entry| entry["loader"]["version"].as_str())
+        .collect();
+
+    versions
+        .iter()
+        .find(|version| !version.contains('-'))
+        .or_else(|| versions.first())
+        .map(|version| version.to_string())
+}
+
+/// Profile of the loader from the launcher profile served by Quilt meta
+pub(super) async fn loader_profile(
+    launcher_profile: Value,
+    version: String,
+) -> Result<LoaderProfile, LoaderError> {
+    let main_class = &launcher_profile["mainClass"];
+    let main_class = match main_class
+        .as_str()
+        .or_else(|| main_class["client"].as_str())
+    {
+        Some(main_class) => main_class.to_string(),
+        None => {
+            return Err(LoaderError::InvalidMeta(format!(
+                "{} {} has no main class",
+                LOADER_UID, version
+            )))
+        }
+    };
+
+    Ok(LoaderProfile {
+        loader: Loader::Quilt,
+        version,
+        main_class,
+        libraries: component_libraries(&launcher_profile).await?,
+        local_libraries: Vec::new(),
+        jvm_args: string_args(&launcher_profile["arguments"]["jvm"]),
+        game_args: string_args(&launcher_profile["arguments"]["game"]),
+        installer: None,
+    })
+}
@@ -0,0 +1,461 @@
+use std::{collections::HashMap, fs::File, io::Write};
+
+use serde_json::json;
+use tempfile::tempdir;
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use super::*;
+
+fn fabric_profile() -> LoaderProfile {
+    LoaderProfile {
+        loader: Loader::Fabric,
+        version: "0.15.11".to_string(),
+        main_class: "net.fabricmc.loader.impl.launch.knot.KnotClient".to_string(),
+        libraries: vec![
+            json!({
+                "name": "net.fabricmc:fabric-loader:0.15.11",
+                "downloads": { "artifact": { "url": "https://maven.fabricmc.net/loader.jar", "sha1": "a1" } }
+            }),
+            json!({
+                "name": "org.ow2.asm:asm:9.6",
+                "downloads": { "artifact": { "url": "https://maven.fabricmc.net/asm.jar", "sha1": "b2" } }
+            }),
+        ],
+        local_libraries: Vec::new(),
+        jvm_args: Vec::new(),
+        game_args: Vec::new(),
+        installer: None,
+    }
+}
+
+#[test]
+fn loader_names() {
+    assert_eq!(Loader::from_name("fabric"), Some(Loader::Fabric));
+    assert_eq!(Loader::from_name(Loader::Quilt.as_str()), Some(Loader::Quilt));
+    assert_eq!(
+        Loader::from_name(Loader::Vanilla.as_str()),
+        Some(Loader::Vanilla)
+    );
+    assert_eq!(Loader::from_name("unknown"), None);
+    assert_eq!(Loader::default(), Loader::Vanilla);
+}
+
+#[test]
+fn profile_is_merged_on_top_of_manifest() {
+    let mut manifest = json!({
+        "version": "1.20.1",
+        "mainClass": "net.minecraft.client.main.Main",
+        "libraries": [
+            { "name": "org.ow2.asm:asm:9.1" },
+            { "name": "com.mojang:brigadier:1.1.8" }
+        ]
+    });
+
+    fabric_profile().merge_into(&mut manifest);
+
+    let names: Vec<&str> = manifest["libraries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|lib| lib["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "net.fabricmc:fabric-loader:0.15.11",
+            "org.ow2.asm:asm:9.6",
+            "com.mojang:brigadier:1.1.8"
+        ]
+    );
+    assert_eq!(
+        manifest["mainClass"],
+        "net.fabricmc.loader.impl.launch.knot.KnotClient"
+    );
+    assert_eq!(manifest["version"], "1.20.1");
+}
+
+#[test]
+fn latest_version_prefers_recommended() {
+    let index = json!({
+        "versions": [
+            { "version": "0.16.0-beta.1", "recommended": false },
+            { "version": "0.15.11", "recommended": true },
+            { "version": "0.15.10", "recommended": true }
+        ]
+    });
+    assert_eq!(latest_version(&index, None).as_deref(), Some("0.15.11"));
+
+    let index = json!({ "versions": [{ "version": "0.1.0" }] });
+    assert_eq!(latest_version(&index, None).as_deref(), Some("0.1.0"));
+
+    assert_eq!(latest_version(&json!({ "versions": [] }), None), None);
+}
+
+#[test]
+fn latest_version_requires_game_version() {
+    let requires = |game: &str| json!([{ "uid": "net.minecraft", "equals": game }]);
+    let index = json!({
+        "versions": [
+            { "version": "49.0.3", "recommended": false, "requires": requires("1.20.2") },
+            { "version": "47.2.1", "recommended": false, "requires": requires("1.20.1") },
+            { "version": "47.2.0", "recommended": true, "requires": requires("1.20.1") }
+        ]
+    });
+
+    assert_eq!(
+        latest_version(&index, Some("1.20.1")).as_deref(),
+        Some("47.2.0")
+    );
+    assert_eq!(
+        latest_version(&index, Some("1.20.2")).as_deref(),
+        Some("49.0.3")
+    );
+    assert_eq!(latest_version(&index, Some("1.19.4")), None);
+}
+
+#[test]
+fn library_is_located_in_its_repository() {
+    let lib = json!({
+        "name": "net.fabricmc:intermediary:1.20.1",
+        "url": "https://maven.fabricmc.net"
+    });
+    let (path, url) = library_url(&lib).unwrap();
+    assert_eq!(
+        path,
+        "net/fabricmc/intermediary/1.20.1/intermediary-1.20.1.jar"
+    );
+    assert_eq!(
+        url,
+        "https://maven.fabricmc.net/net/fabricmc/intermediary/1.20.1/intermediary-1.20.1.jar"
+    );
+
+    let lib = json!({ "name": "org.ow2.asm:asm:9.6" });
+    let (_, url) = library_url(&lib).unwrap();
+    assert_eq!(
+        url,
+        "https://libraries.minecraft.net/org/ow2/asm/asm/9.6/asm-9.6.jar"
+    );
+}
+
+#[test]
+fn library_key_ignores_version() {
+    let key = |name: &str| library_key(&json!({ "name": name }));
+
+    assert_eq!(
+        key("org.ow2.asm:asm:9.6").as_deref(),
+        Some("org.ow2.asm:asm")
+    );
+    assert_eq!(
+        key("org.lwjgl:lwjgl:3.3.1:natives-linux").as_deref(),
+        Some("org.lwjgl:lwjgl:natives-linux")
+    );
+}
+
+#[async_std::test]
+async fn saved_profile_is_used_offline() {
+    let dir = tempdir().unwrap();
+    let paths = InstancePaths::get_required_paths("test", &dir.path().to_path_buf());
+    let profile = fabric_profile();
+    let path = profile_path(
+        paths.meta(),
+        "net.fabricmc.fabric-loader",
+        "0.15.11",
+        "1.20.1",
+    );
+    save_profile(&path, &profile).await.unwrap();
+
+    let resolved = resolve_loader(
+        Loader::Fabric,
+        "1.20.1",
+        Some("0.15.11"),
+        &paths,
+        true,
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(resolved, Some(profile));
+
+    let missing = resolve_loader(
+        Loader::Fabric,
+        "1.19.4",
+        Some("0.15.11"),
+        &paths,
+        true,
+        None,
+    )
+    .await;
+    assert!(matches!(missing, Err(LoaderError::NotCached(_))));
+
+    let forge = resolve_loader(
+        Loader::Forge,
+        "1.20.1",
+        Some("1.20.1-47.2.0"),
+        &paths,
+        true,
+        None,
+    )
+    .await;
+    assert!(matches!(forge, Err(LoaderError::NotCached(_))));
+
+    let vanilla = resolve_loader(Loader::Vanilla, "1.20.1", None, &paths, true, None).await;
+    assert_eq!(vanilla.unwrap(), None);
+}
+
+#[test]
+fn profile_args_are_merged_into_manifest() {
+    let mut profile = fabric_profile();
+    profile.jvm_args = vec!["-DlibraryDirectory=${library_directory}".to_string()];
+    profile.game_args = vec!["--launchTarget".to_string(), "forgeclient".to_string()];
+
+    let mut modern = json!({ "arguments": { "game": ["--username", "${auth_player_name}"] } });
+    profile.merge_into(&mut modern);
+    assert_eq!(
+        modern["+jvmArgs"],
+        json!(["-DlibraryDirectory=${library_directory}"])
+    );
+    assert_eq!(
+        modern["arguments"]["game"],
+        json!([
+            "--username",
+            "${auth_player_name}",
+            "--launchTarget",
+            "forgeclient"
+        ])
+    );
+
+    let mut legacy = json!({ "minecraftArguments": "--username ${auth_player_name}" });
+    profile.merge_into(&mut legacy);
+    assert_eq!(
+        legacy["minecraftArguments"],
+        "--username ${auth_player_name} --launchTarget forgeclient"
+    );
+}
+
+#[test]
+fn installer_coordinates() {
+    let (coord, repository) = forge::installer_coord(Loader::Forge, "1.20.1", "47.2.0");
+    assert_eq!(coord, "net.minecraftforge:forge:1.20.1-47.2.0:installer");
+    assert_eq!(repository, "https://maven.minecraftforge.net/");
+
+    let (coord, _) = forge::installer_coord(Loader::NeoForge, "1.20.4", "20.4.237");
+    assert_eq!(coord, "net.neoforged:neoforge:20.4.237:installer");
+
+    let (coord, repository) = forge::installer_coord(Loader::NeoForge, "1.20.1", "47.1.106");
+    assert_eq!(coord, "net.neoforged:forge:1.20.1-47.1.106:installer");
+    assert_eq!(repository, "https://maven.neoforged.net/releases/");
+}
+
+fn install_profile() -> Value {
+    json!({
+        "json": "/version.json",
+        "data": {
+            "MAPPINGS": { "client": "[de.oceanlabs.mcp:mcp_config:1.20.1:mappings@txt]", "server": "" },
+            "BINPATCH": { "client": "/data/client.lzma", "server": "/data/server.lzma" }
+        },
+        "processors": [
+            { "jar": "net.minecraftforge:installertools:1.3.0", "args": ["--task", "MCP_DATA"] },
+            { "sides": ["server"], "jar": "net.minecraftforge:installertools:1.3.0", "args": [] },
+            {
+                "sides": ["client"],
+                "jar": "net.minecraftforge:binarypatcher:1.1.1",
+                "classpath": ["net.sf.jopt-simple:jopt-simple:5.0.4"],
+                "args": ["--patch", "{BINPATCH}"],
+                "outputs": { "{PATCHED}": "{PATCHED_SHA}" }
+            }
+        ],
+        "libraries": [{ "name": "net.minecraftforge:binarypatcher:1.1.1" }]
+    })
+}
+
+fn version_json() -> Value {
+    json!({
+        "mainClass": "cpw.mods.bootstraplauncher.BootstrapLauncher",
+        "arguments": {
+            "game": ["--launchTarget", "forgeclient"],
+            "jvm": ["-DignoreList=bootstraplauncher,${version_name}.jar", "-p", "${library_directory}"]
+        },
+        "libraries": [
+            {
+                "name": "cpw.mods:bootstraplauncher:1.1.2",
+                "downloads": { "artifact": {
+                    "path": "cpw/mods/bootstraplauncher/1.1.2/bootstraplauncher-1.1.2.jar",
+                    "url": "https://maven.minecraftforge.net/cpw/mods/bootstraplauncher/1.1.2/bootstraplauncher-1.1.2.jar",
+                    "sha1": "a1"
+                } }
+            },
+            {
+                "name": "net.minecraftforge:forge:1.20.1-47.2.0:client",
+                "downloads": { "artifact": {
+                    "path": "net/minecraftforge/forge/1.20.1-47.2.0/forge-1.20.1-47.2.0-client.jar",
+                    "url": ""
+                } }
+            }
+        ]
+    })
+}
+
+#[test]
+fn installer_profile_splits_libraries() {
+    let profile = forge::installer_profile(
+        Loader::Forge,
+        "47.2.0".to_string(),
+        "net/minecraftforge/forge/1.20.1-47.2.0/forge-1.20.1-47.2.0-installer.jar".to_string(),
+        &install_profile(),
+        &version_json(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        profile.main_class,
+        "cpw.mods.bootstraplauncher.BootstrapLauncher"
+    );
+    assert_eq!(profile.libraries.len(), 1);
+    assert_eq!(
+        profile.local_libraries,
+        vec!["net/minecraftforge/forge/1.20.1-47.2.0/forge-1.20.1-47.2.0-client.jar"]
+    );
+    assert_eq!(
+        profile.jvm_args,
+        vec![
+            "-DignoreList=bootstraplauncher,${version_name}-client.jar",
+            "-p",
+            "${library_directory}"
+        ]
+    );
+    assert_eq!(profile.game_args, vec!["--launchTarget", "forgeclient"]);
+
+    let installer = profile.installer.unwrap();
+    assert_eq!(installer.processors.len(), 2);
+    assert_eq!(
+        installer.data.get("BINPATCH").map(String::as_str),
+        Some("/data/client.lzma")
+    );
+    assert_eq!(installer.libraries.len(), 1);
+}
+
+#[async_std::test]
+async fn installer_files_are_read() {
+    let dir = tempdir().unwrap();
+    let installer = dir.path().join("installer.jar");
+    let libs = dir.path().join("libraries");
+
+    let mut zip = ZipWriter::new(File::create(&installer).unwrap());
+    let files = [
+        ("install_profile.json", install_profile().to_string()),
+        ("version.json", version_json().to_string()),
+        (
+            "maven/net/minecraftforge/forge/1.20.1-47.2.0/forge-1.20.1-47.2.0-universal.jar",
+            "jar".to_string(),
+        ),
+        ("data/client.lzma", "patch".to_string()),
+    ];
+    for (name, data) in files {
+        zip.start_file(name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(data.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+
+    let (profile, version) = forge::read_installer(&installer, &libs).await.unwrap();
+    assert_eq!(profile, install_profile());
+    assert_eq!(version, version_json());
+    assert!(libs
+        .join("net/minecraftforge/forge/1.20.1-47.2.0/forge-1.20.1-47.2.0-universal.jar")
+        .exists());
+    assert!(!libs.join("data").exists());
+}
+
+#[async_std::test]
+async fn legacy_installer_is_rejected() {
+    let dir = tempdir().unwrap();
+    let installer = dir.path().join("installer.jar");
+
+    let mut zip = ZipWriter::new(File::create(&installer).unwrap());
+    zip.start_file("install_profile.json", SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(
+        json!({ "install": {}, "versionInfo": {} })
+            .to_string()
+            .as_bytes(),
+    )
+    .unwrap();
+    zip.finish().unwrap();
+
+    let result = forge::read_installer(&installer, dir.path()).await;
+    assert!(matches!(result, Err(LoaderError::InvalidInstaller(_, _))));
+}
+
+#[test]
+fn processor_args_are_substituted() {
+    let libs = Path::new("/libs");
+    let data = HashMap::from([
+        ("SIDE".to_string(), "client".to_string()),
+        ("PATCHED".to_string(), "/libs/client.jar".to_string()),
+    ]);
+    let arg = |arg: &str| processors::substitute_arg(arg, &data, libs);
+
+    assert_eq!(arg("--side").unwrap(), "--side");
+    assert_eq!(arg("{SIDE}").unwrap(), "client");
+    assert_eq!(
+        arg("--output={PATCHED}").unwrap(),
+        "--output=/libs/client.jar"
+    );
+    assert_eq!(
+        arg("[org.ow2.asm:asm:9.6]").unwrap(),
+        libs.join("org/ow2/asm/asm/9.6/asm-9.6.jar")
+            .display()
+            .to_string()
+    );
+    assert!(arg("{MISSING}").is_err());
+}
+
+#[test]
+fn quilt_prefers_stable_loader() {
+    let entry = |version: &str| json!({ "loader": { "maven": "org.quiltmc:quilt-loader", "version": version } });
+
+    let versions = json!([entry("0.26.1-beta.1"), entry("0.26.0"), entry("0.25.0")]);
+    assert_eq!(
+        quilt::latest_loader_version(&versions).as_deref(),
+        Some("0.26.0")
+    );
+
+    let versions = json!([entry("0.1.0-beta.2")]);
+    assert_eq!(
+        quilt::latest_loader_version(&versions).as_deref(),
+        Some("0.1.0-beta.2")
+    );
+
+    assert_eq!(quilt::latest_loader_version(&json!([])), None);
+}
+
+#[async_std::test]
+async fn quilt_launcher_profile_is_converted() {
+    let launcher_profile = json!({
+        "id": "quilt-loader-0.26.0-1.20.1",
+        "inheritsFrom": "1.20.1",
+        "mainClass": "org.quiltmc.loader.impl.launch.knot.KnotClient",
+        "arguments": { "game": [], "jvm": ["-DFabricMcEmu= net.minecraft.client.main.Main "] },
+        "libraries": [{
+            "name": "org.quiltmc:quilt-loader:0.26.0",
+            "downloads": { "artifact": { "url": "https://maven.quiltmc.org/loader.jar", "sha1": "a1" } }
+        }]
+    });
+
+    let profile = quilt::loader_profile(launcher_profile, "0.26.0".to_string())
+        .await
+        .unwrap();
+    assert_eq!(profile.loader, Loader::Quilt);
+    assert_eq!(
+        profile.main_class,
+        "org.quiltmc.loader.impl.launch.knot.KnotClient"
+    );
+    assert_eq!(profile.libraries.len(), 1);
+    assert_eq!(
+        profile.jvm_args,
+        vec!["-DFabricMcEmu= net.minecraft.client.main.Main "]
+    );
+    assert!(profile.game_args.is_empty());
+
+    let invalid = quilt::loader_profile(json!({ "libraries": [] }), "0.26.0".to_string()).await;
+    assert!(matches!(invalid, Err(LoaderError::InvalidMeta(_))));
+}
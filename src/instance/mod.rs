@@ -2,10 +2,13 @@ use async_std::fs::create_dir_all;
 use chrono::Utc;
 use core::str;
 use getset::Getters;
+use launch::supervisor::Supervisor;
+use launch::supervisor::SupervisorError;
 use launch::ClientOptions;
 use launch::LaunchInfo;
 use paths::InstancePaths;
 use serde::Deserialize;
+use std::time::Duration;
 use thiserror::Error;
 
 pub mod download;
@@ -14,16 +17,24 @@ use tide_websockets::WebSocketConnection;
 pub mod init;
 pub mod launch;
 pub mod list;
+pub mod loader;
 pub mod options;
 pub mod paths;
-mod websocket;
+pub mod websocket;
 
 use crate::data::db::DBError;
+use crate::instance::download::assets::AssetSyncError;
+use crate::instance::download::libs::LibsSyncError;
+use crate::instance::loader::Loader;
+use crate::instance::loader::LoaderError;
 use crate::data::db::Database;
 use crate::data::GlobalDataState;
+use crate::instance::options::pages::settings::Settings;
 use crate::instance::options::pages::Page;
+use crate::instance::options::pages::ReadPage;
 use crate::instance::options::ChangeRequestBuilder;
 use crate::instance::options::Options;
+use crate::utils::db::{instance_loader, instance_name};
 use crate::utils::db::register_instance;
 use crate::websocket::messages::option::OptionUpdateMessage;
 use crate::websocket::messages::task::Task;
@@ -38,14 +49,26 @@ pub struct InitData {
     pub name: String,
     pub url: String,
     pub request_id: String,
+    /// Use only saved manifests and downloaded files
+    #[serde(default)]
+    pub offline: bool,
+    /// Mod loader installed on top of the game
+    #[serde(default)]
+    pub loader: Loader,
+    /// The latest recommended version is used when it is not set
+    #[serde(default)]
+    pub loader_version: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct RunData {
+    instance_id: i64,
     name: String,
     url: String,
     request_id: String,
     launch_options: Option<ClientOptions>,
+    #[serde(default)]
+    offline: bool,
 }
 
 #[derive(Debug, Deserialize, Clone, Getters)]
@@ -58,6 +81,10 @@ pub struct Instance {
     version_id: String,
     version_manifest: serde_json::Value,
     #[get = "pub"]
+    loader: Loader,
+    #[get = "pub"]
+    loader_version: Option<String>,
+    #[get = "pub"]
     paths: InstancePaths,
 }
 
@@ -72,6 +99,21 @@ pub enum InstanceError {
     #[error("Failed to run instance: {0}")]
     RunFailed(String),
 
+    #[error("Failed to verify instance files: {0}")]
+    VerificationFailed(String),
+
+    #[error("{0}")]
+    ManifestFetchFailed(String),
+
+    #[error("Failed to download and register libs: {0}")]
+    Libs(#[from] LibsSyncError),
+
+    #[error("Failed to download and register assets: {0}")]
+    Assets(#[from] AssetSyncError),
+
+    #[error("Failed to install mod loader: {0}")]
+    Loader(#[from] LoaderError),
+
     #[error("Instance id is wrong: {0}")]
     WrongId(String),
 
@@ -105,6 +147,12 @@ pub enum InstanceError {
     #[error(transparent)]
     DB(#[from] DBError),
 
+    #[error(transparent)]
+    Process(#[from] SupervisorError),
+
+    #[error("Operation was cancelled")]
+    Cancelled,
+
     #[error("Function is not implemented yet")]
     NotImplemented
 }
@@ -114,13 +162,29 @@ pub type Result<T> = std::result::Result<T, InstanceError>;
 impl<'a> Instance {
     pub async fn run(
         run_data: RunData,
-        req: &EndpointRequest<'a>,
+        req: &EndpointRequest<'static>,
         ws: &WebSocketConnection,
-    ) -> Result<()> {
+    ) -> Result<u32> {
+        // Id is used for the settings and the process of the instance,
+        // so it must belong to the launched one
+        let db = &req.state().static_data.db;
+        if instance_name(db, run_data.instance_id).await?.as_deref() != Some(&run_data.name) {
+            return Err(InstanceError::WrongId(format!(
+                "{} is not the id of {}",
+                run_data.instance_id, run_data.name
+            )));
+        }
+
+        // Loader is launched in the version it was installed with
+        let (loader, loader_version) = instance_loader(db, run_data.instance_id).await?;
+
         let init_data = InitData {
             name: run_data.name,
             url: run_data.url,
             request_id: run_data.request_id,
+            offline: run_data.offline,
+            loader,
+            loader_version,
         };
 
         let (instance, launch_info) =
@@ -132,7 +196,23 @@ impl<'a> Instance {
                 }
             };
 
-        launch::execute::launch_instance(instance.version_manifest, launch_info).await;
+        let settings =
+            Settings::from_db(run_data.instance_id, &req.state().static_data.db).await?;
+
+        let pid = launch::execute::launch_instance(
+            req.state(),
+            run_data.instance_id,
+            instance.version_manifest,
+            launch_info,
+            &settings,
+        )
+        .await?;
+
+        Ok(pid)
+    }
+
+    pub async fn stop(req: &EndpointRequest<'a>, id: i64, grace_period: Duration) -> Result<()> {
+        Supervisor::stop(req.state(), id, grace_period).await?;
         Ok(())
     }
 
@@ -61,6 +61,14 @@ impl Options {
                 Overview::update(f, req, request.id).await?;
             },
             ChangableOptions::Settings(f) => {
+                // Memory which isn't changed by the request keeps its stored value
+                let db = &req.state().static_data.db;
+                let stored = Settings::from_db(request.id, db).await?;
+                check_memory_range(
+                    f.min_memory.unwrap_or(*stored.min_memory()),
+                    f.max_memory.unwrap_or(*stored.max_memory()),
+                )?;
+
                 Settings::update(f, req, request.id).await?
             }
         }
@@ -106,9 +114,24 @@ impl ChangeRequestBuilder {
                     )
                 })?;
 
+                check_memory_range(fields.min_memory.flatten(), fields.max_memory.flatten())?;
+
                 Ok(ChangeRequest { id, change: ChangableOptions::Settings(fields) })
             }
             _ => Err(InstanceError::NotImplemented)
         }
     }
 }
+
+fn check_memory_range(min: Option<u32>, max: Option<u32>) -> Result<(), InstanceError> {
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(InstanceError::OptionNotAvailable(format!(
+                "Minimum memory {}M is greater than maximum memory {}M",
+                min, max
+            )));
+        }
+    }
+
+    Ok(())
+}
@@ -1,5 +1,9 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
+use getset::Getters;
 use serde::{Deserialize, Serialize};
 use tide::utils::async_trait;
 use ts_rs::TS;
@@ -9,9 +13,45 @@ use crate::{
     instance::options::pages::ReadPage, EndpointRequest,
 };
 
-#[derive(Debug, Serialize)]
+/// Value of `java_path` which selects `java` from PATH
+const SYSTEM_JAVA: &str = "java";
+
+#[derive(Debug, Serialize, Getters)]
+#[get = "pub"]
 pub struct Settings {
     dir: PathBuf,
+    min_memory: Option<u32>,
+    max_memory: Option<u32>,
+    java: JavaSelection,
+    jvm_args: Vec<String>,
+    game_args: Vec<String>,
+    env_vars: HashMap<String, String>,
+    window: Option<WindowSize>,
+}
+
+/// Java used to run the instance
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default, TS)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+#[ts(export_to = "./options/settings/")]
+pub enum JavaSelection {
+    /// Runtime required by the version manifest, installed on init
+    #[default]
+    Auto,
+    /// `java` found in PATH
+    System,
+    /// Path to the java executable
+    Path(PathBuf),
+    /// Runtime downloaded by the launcher, e.g. `java-runtime-delta`
+    Managed(String),
+    /// Installation found by the system scan, by its id
+    Detected(i64),
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, TS)]
+#[ts(export_to = "./options/settings/")]
+pub struct WindowSize {
+    pub width: u32,
+    pub height: u32,
 }
 
 #[serde_with::skip_serializing_none]
@@ -20,11 +60,44 @@ pub struct Settings {
 #[ts(export_to = "./options/settings/")]
 pub struct SettingsFields {
     pub dir: Option<PathBuf>,
+
+    // Missing fields are left unchanged, `null` resets them to the default
+
+    /// Memory in MiB
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    #[ts(as = "Option<u32>")]
+    pub min_memory: Option<Option<u32>>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    #[ts(as = "Option<u32>")]
+    pub max_memory: Option<Option<u32>>,
+
+    pub java: Option<JavaSelection>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    #[ts(as = "Option<Vec<String>>")]
+    pub jvm_args: Option<Option<Vec<String>>>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    #[ts(as = "Option<Vec<String>>")]
+    pub game_args: Option<Option<Vec<String>>>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    #[ts(as = "Option<HashMap<String, String>>")]
+    pub env_vars: Option<Option<HashMap<String, String>>>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    #[ts(as = "Option<WindowSize>")]
+    pub window: Option<Option<WindowSize>>,
 }
 
 impl From<Settings> for SettingsFields {
     fn from(s: Settings) -> Self {
-        SettingsFields { dir: Some(s.dir) }
+        SettingsFields {
+            dir: Some(s.dir),
+            min_memory: Some(s.min_memory),
+            max_memory: Some(s.max_memory),
+            java: Some(s.java),
+            jvm_args: Some(Some(s.jvm_args)),
+            game_args: Some(Some(s.game_args)),
+            env_vars: Some(Some(s.env_vars)),
+            window: Some(s.window),
+        }
     }
 }
 
@@ -35,15 +108,64 @@ impl Settings {
             None => None
         };
 
+        // Java columns are replaced together, so the selection can be reset to the automatic one
+        let java_changed = change.java.is_some();
+        let (java_path, java_runtime, system_java_id) = match change.java {
+            Some(JavaSelection::System) => (Some(SYSTEM_JAVA.to_string()), None, None),
+            Some(JavaSelection::Path(path)) => (Some(path.display().to_string()), None, None),
+            Some(JavaSelection::Managed(runtime)) => (None, Some(runtime), None),
+            Some(JavaSelection::Detected(id)) => (None, None, Some(id)),
+            Some(JavaSelection::Auto) | None => (None, None, None),
+        };
+
+        // Nullable columns are replaced when the field is present, even with `null`
+        let min_memory_changed = change.min_memory.is_some();
+        let min_memory = change.min_memory.flatten();
+        let max_memory_changed = change.max_memory.is_some();
+        let max_memory = change.max_memory.flatten();
+        let window_changed = change.window.is_some();
+        let window = change.window.flatten();
+        let window_width = window.map(|w| w.width);
+        let window_height = window.map(|w| w.height);
+
+        // `null` args and variables are reset to empty ones
+        let jvm_args = change.jvm_args.map(|v| to_json(&v.unwrap_or_default())).transpose()?;
+        let game_args = change.game_args.map(|v| to_json(&v.unwrap_or_default())).transpose()?;
+        let env_vars = change.env_vars.map(|v| to_json(&v.unwrap_or_default())).transpose()?;
+
         let db = &req.state().static_data.db;
         sqlx::query!(
             r#"
             UPDATE instances_settings
             SET
-                dir = COALESCE($1, dir)
-            WHERE instance_id = $2
+                dir = COALESCE($1, dir),
+                min_memory = CASE WHEN $2 THEN $3 ELSE min_memory END,
+                max_memory = CASE WHEN $4 THEN $5 ELSE max_memory END,
+                java_path = CASE WHEN $6 THEN $7 ELSE java_path END,
+                java_runtime = CASE WHEN $6 THEN $8 ELSE java_runtime END,
+                system_java_id = CASE WHEN $6 THEN $9 ELSE system_java_id END,
+                jvm_args = COALESCE($10, jvm_args),
+                game_args = COALESCE($11, game_args),
+                env_vars = COALESCE($12, env_vars),
+                window_width = CASE WHEN $13 THEN $14 ELSE window_width END,
+                window_height = CASE WHEN $13 THEN $15 ELSE window_height END
+            WHERE instance_id = $16
             "#,
             dir,
+            min_memory_changed,
+            min_memory,
+            max_memory_changed,
+            max_memory,
+            java_changed,
+            java_path,
+            java_runtime,
+            system_java_id,
+            jvm_args,
+            game_args,
+            env_vars,
+            window_changed,
+            window_width,
+            window_height,
             instance_id
         ).execute(&db.pool).await?;
 
@@ -73,10 +195,11 @@ impl Settings {
 #[async_trait]
 impl ReadPage for Settings {
     async fn from_db(instance_id: i64, db: &Database) -> Result<Self> {
-        let rec = sqlx::query_as!(
-            Settings,
+        let rec = sqlx::query!(
             r#"
-                SELECT dir
+                SELECT
+                    dir, min_memory, max_memory, java_path, java_runtime, system_java_id,
+                    jvm_args, game_args, env_vars, window_width, window_height
                 FROM instances_settings
                 WHERE instance_id = ?
                 "#,
@@ -85,7 +208,47 @@ impl ReadPage for Settings {
         .fetch_optional(&db.pool)
         .await?;
 
-        let settings = rec.ok_or_else(|| DBError::NotFound(format!("Overview page not found")))?;
+        let rec = rec.ok_or_else(|| DBError::NotFound("Settings page not found".to_string()))?;
+
+        let java = match (rec.java_path, rec.java_runtime, rec.system_java_id) {
+            (Some(path), _, _) if path == SYSTEM_JAVA => JavaSelection::System,
+            (Some(path), _, _) => JavaSelection::Path(PathBuf::from(path)),
+            (None, Some(runtime), _) => JavaSelection::Managed(runtime),
+            (None, None, Some(id)) => JavaSelection::Detected(id),
+            (None, None, None) => JavaSelection::Auto,
+        };
+
+        let window = match (rec.window_width, rec.window_height) {
+            (Some(width), Some(height)) => Some(WindowSize {
+                width: to_u32(width)?,
+                height: to_u32(height)?,
+            }),
+            _ => None,
+        };
+
+        let settings = Settings {
+            dir: PathBuf::from(rec.dir),
+            min_memory: rec.min_memory.map(to_u32).transpose()?,
+            max_memory: rec.max_memory.map(to_u32).transpose()?,
+            java,
+            jvm_args: from_json(&rec.jvm_args)?,
+            game_args: from_json(&rec.game_args)?,
+            env_vars: from_json(&rec.env_vars)?,
+            window,
+        };
+
         Ok(settings)
     }
 }
+
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|e| DBError::InvalidInput(e.to_string()))
+}
+
+fn from_json<T: for<'de> Deserialize<'de>>(value: &str) -> Result<T> {
+    serde_json::from_str(value).map_err(|_| DBError::ResultCorrupted)
+}
+
+fn to_u32(value: i64) -> Result<u32> {
+    u32::try_from(value).map_err(|_| DBError::ResultCorrupted)
+}
@@ -1,4 +1,5 @@
 use crate::instance::options::pages::overview::ExportTypes;
+use crate::instance::options::pages::settings::{JavaSelection, WindowSize};
 
 use super::*;
 
@@ -160,3 +161,94 @@ fn parse_change_requets_different_case() {
 
     assert!(matches!(req_builded.change, ChangableOptions::Overview(_)));
 }
+
+#[test]
+fn parse_change_request_settings() {
+    let raw = r#"
+        {
+            "id": 3,
+            "page": "settings",
+            "options": {
+                "min_memory": 1024,
+                "max_memory": 4096,
+                "java": { "type": "managed", "value": "java-runtime-delta" },
+                "jvm_args": ["-XX:+UseG1GC"],
+                "env_vars": { "MESA_GL_VERSION_OVERRIDE": "4.5" },
+                "window": { "width": 1280, "height": 720 }
+            }
+        }
+    "#;
+
+    let req_builder: ChangeRequestBuilder = serde_json::from_str(raw).unwrap();
+    let req_builded = req_builder.build().unwrap();
+
+    match req_builded.change {
+        ChangableOptions::Settings(fields) => {
+            assert_eq!(fields.min_memory, Some(Some(1024)));
+            assert_eq!(fields.max_memory, Some(Some(4096)));
+            assert_eq!(fields.java, Some(JavaSelection::Managed(String::from("java-runtime-delta"))));
+            assert_eq!(fields.jvm_args, Some(Some(vec![String::from("-XX:+UseG1GC")])));
+            assert!(fields.game_args.is_none());
+            assert_eq!(fields.window, Some(Some(WindowSize { width: 1280, height: 720 })));
+        },
+        _ => {
+            panic!("Expected SettingsFields");
+        }
+    }
+}
+
+#[test]
+fn parse_change_request_settings_reset() {
+    let raw = r#"
+        {
+            "id": 3,
+            "page": "settings",
+            "options": {
+                "max_memory": null,
+                "jvm_args": null,
+                "window": null
+            }
+        }
+    "#;
+
+    let req_builder: ChangeRequestBuilder = serde_json::from_str(raw).unwrap();
+    let req_builded = req_builder.build().unwrap();
+
+    match req_builded.change {
+        ChangableOptions::Settings(fields) => {
+            assert_eq!(fields.max_memory, Some(None));
+            assert_eq!(fields.jvm_args, Some(None));
+            assert_eq!(fields.window, Some(None));
+            assert!(fields.min_memory.is_none());
+            assert!(fields.env_vars.is_none());
+        },
+        _ => {
+            panic!("Expected SettingsFields");
+        }
+    }
+}
+
+#[test]
+fn parse_change_request_settings_wrong_memory() {
+    let raw = r#"
+        {
+            "id": 3,
+            "page": "settings",
+            "options": {
+                "min_memory": 4096,
+                "max_memory": 1024
+            }
+        }
+    "#;
+
+    let req_builder: ChangeRequestBuilder = serde_json::from_str(raw).unwrap();
+
+    if let Err(e) = req_builder.build() {
+        assert_eq!(
+            e.to_string(),
+            "Failed to read option: Minimum memory 4096M is greater than maximum memory 1024M".to_string()
+        );
+    } else {
+        panic!("Should return error about incorrect memory range");
+    }
+}
@@ -15,7 +15,6 @@ pub struct InstancePaths {
     headers: PathBuf,
     meta: PathBuf,
     version_manifest_file: Option<PathBuf>,
-    metacache_file: PathBuf,
 }
 
 impl InstancePaths {
@@ -30,11 +29,20 @@ impl InstancePaths {
             headers: launcher_root.join("headers"),
             meta: launcher_root.join("meta"),
             version_manifest_file: None,
-            metacache_file: launcher_root.join("metacache.json"),
             root: launcher_root.clone(),
         }
     }
 
+    /// Directory native libs of the instance are extracted to
+    pub fn natives(&self) -> PathBuf {
+        self.instance.join("natives")
+    }
+
+    /// Directory managed Java runtimes are installed to
+    pub fn java(&self) -> PathBuf {
+        self.root.join("java")
+    }
+
     pub fn set_version_manifest_file<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
         self.version_manifest_file = Some(path.as_ref().into());
         self
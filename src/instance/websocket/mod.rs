@@ -1,19 +1,27 @@
 use std::sync::Arc;
 
+use async_channel::Receiver;
 use async_std::sync::Mutex;
 use chrono::Utc;
 use serde_json::json;
 use surf::utils::async_trait;
 use tide_websockets::WebSocketConnection;
 
-use crate::websocket::messages::{
-    operation::{
-        event::{OperationEvent, OperationStart, OperationUpdate},
-        process::{ProcessStatus, ProcessTarget},
-        stage::{OperationStage, StageError, StageResult, StageStatus},
-        OperationMessage,
+use crate::{
+    instance::loader::Loader,
+    utils::download::progress::DownloadProgress,
+    websocket::messages::{
+        operation::{
+            event::{
+                OperationEvent, OperationFinish, OperationStart, OperationStatus, OperationUpdate,
+            },
+            process::{FileStatus, ProcessStatus, ProcessTarget},
+            progress::ProgressUnit,
+            stage::{OperationStage, StageError, StageResult, StageStatus},
+            OperationMessage,
+        },
+        BaseMessage, WsMessage,
     },
-    BaseMessage, WsMessage,
 };
 
 // type Result<T> = std::result::Result<T, WsMessageError>;
@@ -60,20 +68,51 @@ impl<'a> OperationWsMessage<'a> {
         guard.send().await;
     }
 
+    /// Starts the init operation. `operation_id` is the id of the internal task,
+    /// which is used to cancel the operation.
+    /// `verify` adds the stage which checks files downloaded before
     pub async fn create_init_task(
         ws: &'a WebSocketConnection,
         request_id: &'a str,
+        operation_id: usize,
+        verify: bool,
+        loader: Loader,
     ) -> Arc<Mutex<Self>> {
-        let stages = vec![
+        let mut stages = vec![
             OperationStage::FetchManifest,
             OperationStage::DownloadLibs,
             OperationStage::DownloadAssets,
+            OperationStage::InstallJava,
         ];
 
+        // Files are verified before the missing ones are downloaded
+        if verify {
+            stages.insert(1, OperationStage::VerifyFiles);
+        }
+
+        // Loader is put on top of the manifest before anything is downloaded
+        if loader != Loader::Vanilla {
+            stages.insert(1, OperationStage::InstallLoader);
+        }
+        // Processors are run with the Java of the game
+        if loader.has_installer() {
+            stages.push(OperationStage::RunProcessors);
+        }
+
+        Self::create_task(ws, request_id, operation_id, stages).await
+    }
+
+    /// Starts the operation consisting of `stages`
+    pub async fn create_task(
+        ws: &'a WebSocketConnection,
+        request_id: &'a str,
+        operation_id: usize,
+        stages: Vec<OperationStage>,
+    ) -> Arc<Mutex<Self>> {
         let op_msg = OperationMessage {
             base: BaseMessage {
                 message_id: "asd".into(),
-                operation_id: Some("todo".to_string()),
+                operation_id: Some(operation_id.to_string()),
                 request_id: Some(request_id.to_string()),
                 timestamp: Utc::now(),
                 correlation_id: None,
@@ -85,6 +124,28 @@ impl<'a> OperationWsMessage<'a> {
         wrapper.send().await;
         Arc::new(Mutex::new(wrapper))
     }
+
+    pub async fn finish_operation(
+        ws: &'a WebSocketConnection,
+        request_id: &'a str,
+        operation_id: usize,
+        status: OperationStatus,
+        error: Option<StageError>,
+    ) {
+        let op_msg = OperationMessage {
+            base: BaseMessage {
+                message_id: "asd".into(),
+                operation_id: Some(operation_id.to_string()),
+                request_id: Some(request_id.to_string()),
+                timestamp: Utc::now(),
+                correlation_id: None,
+            },
+            data: OperationFinish { status, error }.into(),
+        };
+
+        let wrapper: OperationWsMessage = (op_msg, ws).into();
+        wrapper.send().await;
+    }
 }
 
 #[async_trait]
@@ -107,6 +168,10 @@ pub trait OperationWsExt<'a> {
         total: usize,
     ) -> Self;
 
+    /// Progress of a single target inside the stage, e.g. bytes of the file
+    async fn update_single_determinable(self, stage: OperationStage, target: ProcessTarget)
+        -> Self;
+
     async fn complete_stage(
         self,
         status: StageStatus,
@@ -175,6 +240,24 @@ impl<'a> OperationWsExt<'a> for Arc<Mutex<OperationWsMessage<'a>>> {
         self
     }
 
+    async fn update_single_determinable(
+        self,
+        stage: OperationStage,
+        target: ProcessTarget,
+    ) -> Self {
+        OperationWsMessage::update_and_send(
+            &self,
+            OperationUpdate::SingleDeterminable {
+                stage,
+                status: ProcessStatus::InProgress,
+                target: Some(target),
+            },
+        )
+        .await;
+
+        self
+    }
+
     async fn complete_stage(
         self,
         status: StageStatus,
@@ -196,3 +279,26 @@ impl<'a> OperationWsExt<'a> for Arc<Mutex<OperationWsMessage<'a>>> {
         self
     }
 }
+
+/// Sends byte progress of the downloads of the stage until all senders are dropped
+pub async fn forward_download_progress<'a>(
+    ws_status: OperationWsMessageLocked<'a>,
+    stage: OperationStage,
+    progress: Receiver<DownloadProgress>,
+) {
+    let mut ws_status = ws_status;
+
+    while let Ok(event) = progress.recv().await {
+        let target = ProcessTarget::file_with_details(
+            event.name,
+            FileStatus::Downloading,
+            Some(ProgressUnit::Bytes),
+            Some(event.current),
+            event.size,
+        );
+
+        ws_status = ws_status
+            .update_single_determinable(stage.clone(), target)
+            .await;
+    }
+}
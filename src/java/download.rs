@@ -1,16 +1,18 @@
 use async_std::fs::create_dir_all;
-use std::collections::HashSet;
-use std::fs::{File, OpenOptions};
+use std::fs::OpenOptions;
+use std::os::unix::fs::symlink;
+use std::path::Path;
 use std::io::{
     Write,
     BufReader,
     Cursor
 };
 
-use serde_json::json;
-
-use crate::utils::download::download;
-use super::{Java, EntryInfo};
+use crate::utils::{
+    cancel::CancellationToken,
+    download::{download_with_progress, progress::ProgressSender},
+};
+use super::EntryInfo;
 
 
 pub enum DownloadTypes {
@@ -18,7 +20,13 @@ pub enum DownloadTypes {
     Raw,
 }
 
-pub async fn download_java_part(url: &str, download_type: DownloadTypes, path: &String) -> Result<(), String> {
+pub async fn download_java_part(
+    url: &str,
+    download_type: DownloadTypes,
+    path: &String,
+    cancel: &CancellationToken,
+    progress: Option<&ProgressSender>,
+) -> Result<(), String> {
     let path_to_file = &path[..path.rfind('/').unwrap()];
     let file_name = &path[path.rfind('/').unwrap() + 1..];
 
@@ -27,17 +35,18 @@ pub async fn download_java_part(url: &str, download_type: DownloadTypes, path: &
         Err(e) => return Err(e.to_string()),
     };
 
+    let data = match download_with_progress(url, path, progress, Some(cancel)).await {
+        Ok(data) => data,
+        Err(e) => return Err(e),
+    };
+
     let mut file = OpenOptions::new()
         .read(true)
         .write(true)
         .create(true)
+        .truncate(true)
         .open(path_to_file.to_owned() + "/" + file_name).unwrap();
 
-    let data = match download(url.to_string()).await {
-        Ok(data) => data,
-        Err(e) => return Err(e),
-    };
-
     let file_buffer = match download_type {
         DownloadTypes::LZMA => {
             let mut data = BufReader::new(Cursor::new(data));
@@ -61,59 +70,18 @@ pub async fn download_java_part(url: &str, download_type: DownloadTypes, path: &
     Ok(())
 }
 
-pub async fn register_java(
-    metacache: &mut serde_json::Value,
-    metacache_file: String,
-    java: &Java,
-    downloaded_paths: HashSet<EntryInfo>,
-    exec_path: &String
-) -> Result<(), String> {
-    if metacache["javas"].as_array_mut() == None || metacache["javas"].as_array_mut().unwrap().len() == 0 {
-        metacache["javas"] = json!([]);
-
-        let mut insertion = json!({
-            "runtime-type": java.runtime_name,
-            "version": java.version,
-            "exec_path": exec_path,
-            "sha1": java.sha1,
-            "paths": []
-        });
-
-        for item in downloaded_paths {
-            insertion["paths"].as_array_mut().unwrap().push(json!({
-                "path": item.path,
-                "path_type": item.path_type,
-            }));
-        }
+/// Create the symlink of the runtime, replacing the existing file
+pub fn create_link(path: &str, target: &str) -> Result<(), String> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.to_string()),
+        _ => (),
+    };
 
-        metacache["javas"].as_array_mut().unwrap().push(insertion);
-    } else {
-        if let Some(existing_javas) = metacache["javas"].as_array_mut() {
-
-            for existing_java in existing_javas {
-                if let Some(existing_sha1) = existing_java["sha1"].as_str() {
-                    if existing_sha1 == java.sha1 {
-                        if let Some(existing_paths) = existing_java["paths"].as_array_mut() {
-
-                            for item in downloaded_paths {
-                                existing_paths.push(json!({
-                                    "path": item.path,
-                                    "path_type": item.path_type,
-                                }));
-                            }
-                        }
-
-                        break;
-                    }
-                }
-            }
-        }
+    if let Some(parent) = Path::new(path).parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
 
-    let mut metacache_file = File::create(&metacache_file).unwrap();
-    metacache_file.write_all(serde_json::to_string_pretty(&metacache).unwrap().as_bytes()).unwrap();
-
-    Ok(())
+    symlink(target, path).map_err(|e| e.to_string())
 }
 
 pub async fn get_java_part
@@ -121,6 +89,8 @@ pub async fn get_java_part
     downloads: serde_json::Map<String, serde_json::Value>,
     file_type: &str,
     path: &String,
+    cancel: &CancellationToken,
+    progress: Option<&ProgressSender>,
 ) -> Result<EntryInfo, String> {
     let (download_type, url) = if let Some(file_url) = downloads.get("lzma").and_then(|v| v["url"].as_str()) {
         (DownloadTypes::LZMA, file_url)
@@ -129,15 +99,16 @@ pub async fn get_java_part
     } else {
         return Err(format!("Failed to determine download type"));
     };
+    let hash = downloads.get("raw").and_then(|v| v["sha1"].as_str()).map(String::from);
 
     match file_type {
         "file" => {
-            match download_java_part(url, download_type, path).await {
+            match download_java_part(url, download_type, path, cancel, progress).await {
                 Ok(_) => (),
                 Err(e) => return Err(e),
             }
 
-            return Ok(EntryInfo { path: path.to_string(), path_type: "file".to_string() })
+            Ok(EntryInfo { path: path.to_string(), path_type: "file".to_string(), hash })
         },
         "directory" => {
             match create_dir_all(&path).await {
@@ -145,14 +116,14 @@ pub async fn get_java_part
                 Err(e) => return Err(e.to_string()),
             }
 
-            return Ok(EntryInfo { path: path.to_string(), path_type: "directory".to_string() })
+            Ok(EntryInfo { path: path.to_string(), path_type: "directory".to_string(), hash: None })
         },
         "link" => {
-            return Ok(EntryInfo { path: path.to_string(), path_type: "link".to_string() })
+            Ok(EntryInfo { path: path.to_string(), path_type: "link".to_string(), hash: None })
         }
         _ => {
             println!("Unsupported file type");
-            return Err("".to_string())
+            Err("".to_string())
         },
     }
 }
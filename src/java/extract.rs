@@ -1,5 +1,7 @@
+use async_std::fs::remove_file;
 use async_std::task::{self, JoinHandle};
 use std::collections::HashSet;
+use std::path::Path;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
 
@@ -10,11 +12,15 @@ use super::{
     download,
 };
 
-use crate::utils::download::download_in_json;
+use super::runtime::JavaRuntime;
+use crate::{
+    data::db::Database,
+    utils::{cancel::CancellationToken, download::download_in_json},
+};
 
 
-pub async fn start_extraction(metacache_path: String, metacache_value: &mut serde_json::Value, java: &mut Java) -> Result<String, String> {
-    let (java_manifest, local_paths): (serde_json::Value, Vec<String>) = match parse_main_manifest(&metacache_value, java).await {
+pub async fn start_extraction(db: &Database, java: &mut Java, cancel: &CancellationToken) -> Result<String, String> {
+    let (java_manifest, local_paths): (serde_json::Value, Vec<String>) = match parse_main_manifest(db, java).await {
         Ok(data) => {
             if let Some(cdata) = data.0 {
                 cdata
@@ -29,7 +35,7 @@ pub async fn start_extraction(metacache_path: String, metacache_value: &mut serd
         Err(e) => return Err(e),
     };
 
-    match parse_java_manifest(java_manifest, &java, metacache_path, metacache_value, local_paths).await {
+    match parse_java_manifest(java_manifest, java, db, local_paths, cancel).await {
         Ok(data) => {
             if let Some(exec_file) = data {
                 println!("Downloaded");
@@ -44,12 +50,16 @@ pub async fn start_extraction(metacache_path: String, metacache_value: &mut serd
 
 async fn process_futures
 (
-    futures: &mut FuturesUnordered<JoinHandle<Option<EntryInfo>>>,
+    futures: &mut FuturesUnordered<JoinHandle<Result<EntryInfo, String>>>,
     downloaded_paths: &mut HashSet<EntryInfo>,
+    failed_paths: &mut Vec<String>,
 ) {
     while let Some(result) = futures.next().await {
-        if let Some(entry_info) = result {
-            downloaded_paths.insert(entry_info);
+        match result {
+            Ok(entry_info) => {
+                downloaded_paths.insert(entry_info);
+            }
+            Err(path) => failed_paths.push(path),
         }
     }
 }
@@ -57,16 +67,21 @@ async fn process_futures
 pub async fn parse_java_manifest(
     manifest: serde_json::Value,
     java: &Java,
-    metacache_path: String,
-    metacache: &mut serde_json::Value,
-    local_paths: Vec<String>
+    db: &Database,
+    local_paths: Vec<String>,
+    cancel: &CancellationToken,
 ) -> Result<Option<String>, String> {
     let mut exec_file = None;
     let mut downloaded_paths: HashSet<EntryInfo> = HashSet::new();
-    let mut futures: FuturesUnordered<JoinHandle<Option<EntryInfo>>> = FuturesUnordered::new();
+    let mut failed_paths: Vec<String> = Vec::new();
+    let mut futures: FuturesUnordered<JoinHandle<Result<EntryInfo, String>>> = FuturesUnordered::new();
 
     if let Some(files) = manifest["files"].as_object() {
         for file in files {
+            if cancel.is_cancelled() {
+                break;
+            }
+
             let raw_path = file.0.to_string();
             let global_path = format!("{}/{}", java.destination, &raw_path);
 
@@ -82,15 +97,15 @@ pub async fn parse_java_manifest(
 
             if let Some(path_type) = file.1["type"].as_str() {
 
-                // Check for existing path in metacache
+                // Check for files registered by the previous installation
                 let mut found = false;
                 if local_paths.len() > 0 {
+                    let destination = format!("{}/", java.destination);
                     for local_path in &local_paths {
-                        let raw_path = format!("{}/java/", metacache_path[..metacache_path.rfind('/').unwrap()].to_string());
-                        let path = &local_path[raw_path.len()..].to_string();
+                        let path = local_path.strip_prefix(&destination).unwrap_or(local_path);
 
                         // We need to get a local path from global path to compare with manifest path
-                        if path == file.0 {
+                        if path == file.0 && Path::new(local_path).symlink_metadata().is_ok() {
                             found = true;
                             break
                         }
@@ -104,33 +119,68 @@ pub async fn parse_java_manifest(
                 if let Some(downloads) = file.1["downloads"].as_object() {
                     let downloads = downloads.to_owned();
                     let path_type = path_type.to_string();
+                    let cancel = cancel.clone();
+                    let progress = java.progress.clone();
 
                     futures.push(task::spawn(async move {
-                        match download::get_java_part(downloads, &path_type, &global_path).await {
-                            Ok(data) => Some(data),
-                            Err(_e) => None,
+                        // Retries are already exhausted here
+                        match download::get_java_part(downloads, &path_type, &global_path, &cancel, progress.as_ref()).await {
+                            Ok(data) => Ok(data),
+                            Err(e) => {
+                                eprintln!("Failed to download {}: {}", global_path, e);
+                                Err(global_path)
+                            }
                         }
                     }));
 
                     if futures.len() >= 100 {
-                        process_futures(&mut futures, &mut downloaded_paths).await;
+                        process_futures(&mut futures, &mut downloaded_paths, &mut failed_paths).await;
                     }
                 } else {
+                    if path_type == "link" {
+                        let target = file.1["target"].as_str().unwrap_or_default();
+                        if let Err(e) = download::create_link(&global_path, target) {
+                            eprintln!("Failed to create link {}: {}", global_path, e);
+                            continue;
+                        }
+                    }
+
                     if path_type == "link" || path_type == "directory" {
-                        downloaded_paths.insert(EntryInfo { path: global_path, path_type: path_type.to_string() });
+                        downloaded_paths.insert(EntryInfo { path: global_path, path_type: path_type.to_string(), hash: None });
                     }
                 }
             }
         }
     }
 
-    process_futures(&mut futures, &mut downloaded_paths).await;
+    process_futures(&mut futures, &mut downloaded_paths, &mut failed_paths).await;
+
+    // Runtime is incomplete, so don't register it and remove files of this run
+    if cancel.is_cancelled() {
+        for entry in downloaded_paths.iter().filter(|e| e.path_type == "file") {
+            if let Err(e) = remove_file(&entry.path).await {
+                eprintln!("Failed to remove {}: {}", entry.path, e);
+            }
+        }
+
+        return Err("Java installation was cancelled".to_string());
+    }
+
+    // Runtime without some of its files is broken, so it isn't registered
+    if !failed_paths.is_empty() {
+        return Err(format!(
+            "Failed to download {} files of java: {}",
+            failed_paths.len(),
+            failed_paths.join(", ")
+        ));
+    }
 
     if let Some(exec_file) = exec_file {
-        match download::register_java(metacache, metacache_path, java, downloaded_paths, &exec_file).await {
-            Ok(data) => data,
-            Err(e) => return Err(e),
-        };
+        // Platform is checked while parsing the main manifest
+        let arch = arch::manifest_osarch().unwrap_or_default();
+        if let Err(e) = JavaRuntime::register(db, java, arch, &exec_file, &downloaded_paths).await {
+            return Err(format!("Failed to register java runtime: {e}"));
+        }
 
         Ok(Some(exec_file))
     } else {
@@ -138,9 +188,9 @@ pub async fn parse_java_manifest(
     }
 }
 
-async fn parse_main_manifest
+pub async fn parse_main_manifest
 (
-    metacache: &serde_json::Value,
+    db: &Database,
     java: &mut Java
 ) -> Result<
         (Option<(serde_json::Value, Vec<String>)>, Option<String>),
@@ -163,8 +213,14 @@ async fn parse_main_manifest
         if let Some(runtime) = macos[&java.runtime_name].as_array() {
             if let Some(version_name) = runtime[0]["version"]["name"].as_str() {
                 let version_name = version_name.to_string();
-                let major_version = &version_name[..version_name.find('.').unwrap()];
+                // Version names look like `21.0.3` or `8u51`
+                let major_version = version_name
+                    .split(|c: char| !c.is_ascii_digit())
+                    .next()
+                    .unwrap_or_default();
                 if major_version == java.version {
+                    java.version_name = version_name.clone();
+
                     if let Some(manifest_url) = runtime[0]["manifest"]["url"].as_str() {
 
                         // Check java installation
@@ -172,28 +228,15 @@ async fn parse_main_manifest
                             java.sha1 = java_sha1.to_string();
                             let mut local_paths = Vec::new();
 
-                            if let Some(javas) = metacache["javas"].as_array() {
-                                for java in javas {
-                                    if let Some(downloaded_java_sha1) = java["sha1"].as_str() {
-                                        if downloaded_java_sha1 == java_sha1 {
-                                           if let Some(paths) = java["paths"].as_array() {
-                                               // println!("{}", paths.len());
-                                               for path in paths {
-                                                   if let Some(inner_path) = path["path"].as_str() {
-                                                       local_paths.push(inner_path.to_string());
-                                                   }
-                                               }
-                                           }
-
-                                            // if let Some(exec_path) = java["exec_path"].as_str() {
-                                            //     println!("This java is already installed");
-                                            //     return Ok((None, Some(exec_path.to_string())));
-                                            // }
-
-                                            break;
-                                        }
-                                    }
-                                }
+                            // Files of the same build, which were downloaded earlier
+                            let installed = JavaRuntime::find(db, &java.runtime_name, osarch)
+                                .await
+                                .map_err(|e| e.to_string())?;
+                            if let Some(installed) = installed.filter(|j| j.manifest_sha1 == java_sha1) {
+                                let files = JavaRuntime::files(db, installed.id)
+                                    .await
+                                    .map_err(|e| e.to_string())?;
+                                local_paths.extend(files.into_iter().map(|f| f.path));
                             }
 
                             match download_in_json(manifest_url).await {
@@ -0,0 +1,131 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use async_std::fs;
+
+use crate::data::db::Database;
+
+use super::{arch, runtime::JavaRuntime, EntryInfo, Java};
+
+const METACACHE_FILE: &str = "metacache.json";
+
+/// Runtime installed by the launcher before runtimes were kept in DB
+#[derive(Debug, PartialEq)]
+pub struct LegacyRuntime {
+    pub component: String,
+    pub version: String,
+    /// Directory the runtime is installed to
+    pub path: String,
+    pub exec_path: String,
+    pub sha1: String,
+    pub entries: HashSet<EntryInfo>,
+}
+
+/// Register runtimes listed in `metacache.json` of the launcher root.
+/// The file is renamed afterwards, so it is imported only once
+pub async fn import(db: &Database, launcher_root: &Path) {
+    let path = launcher_root.join(METACACHE_FILE);
+    let content = match fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    let runtimes = match serde_json::from_str(&content) {
+        Ok(metacache) => legacy_runtimes(&metacache),
+        Err(e) => {
+            eprintln!("Skipping invalid {}: {}", path.display(), e);
+            Vec::new()
+        }
+    };
+
+    let arch = arch::manifest_osarch().unwrap_or_default();
+    for runtime in runtimes {
+        // Runtimes removed by the user are downloaded again when required
+        if !Path::new(&runtime.exec_path).exists() {
+            continue;
+        }
+
+        let mut java = Java::new(runtime.version.clone(), runtime.component, runtime.path);
+        java.version_name = runtime.version;
+        java.sha1 = runtime.sha1;
+
+        if let Err(e) =
+            JavaRuntime::register(db, &java, arch, &runtime.exec_path, &runtime.entries).await
+        {
+            // The file is kept, so the import is retried on the next start
+            eprintln!("Failed to import java runtime {}: {}", java.runtime_name, e);
+            return;
+        }
+    }
+
+    let imported = path.with_extension("json.imported");
+    if let Err(e) = fs::rename(&path, &imported).await {
+        eprintln!("Failed to rename {}: {}", path.display(), e);
+    }
+}
+
+/// Reads the `javas` array of the metacache.
+/// Hashes of the files weren't stored, they are filled in by verification
+pub fn legacy_runtimes(metacache: &serde_json::Value) -> Vec<LegacyRuntime> {
+    metacache["javas"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|java| {
+            let entries = java["paths"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| {
+                    Some(EntryInfo {
+                        path: entry["path"].as_str()?.to_string(),
+                        path_type: entry["path_type"].as_str()?.to_string(),
+                        hash: None,
+                    })
+                })
+                .collect();
+
+            let exec_path = java["exec_path"].as_str()?.to_string();
+
+            Some(LegacyRuntime {
+                component: java["runtime-type"].as_str()?.to_string(),
+                version: java["version"].as_str().unwrap_or_default().to_string(),
+                path: install_dir(&exec_path, &entries)?,
+                exec_path,
+                sha1: java["sha1"].as_str().unwrap_or_default().to_string(),
+                entries,
+            })
+        })
+        .collect()
+}
+
+/// Runtimes were installed right into the `java` dir of the launcher root,
+/// so the dir is found from the stored paths instead of the component.
+/// Every file of the runtime is inside of the dir, so it is the deepest common parent
+fn install_dir(exec_path: &str, entries: &HashSet<EntryInfo>) -> Option<String> {
+    let parents = entries
+        .iter()
+        .filter_map(|entry| Path::new(&entry.path).parent())
+        .collect::<Vec<_>>();
+
+    // Without entries the executable is expected in the `bin` dir of the runtime
+    let exec_dir = Path::new(exec_path).parent()?;
+    let mut dir: PathBuf = if parents.is_empty() {
+        exec_dir.parent()?.to_path_buf()
+    } else {
+        exec_dir.to_path_buf()
+    };
+    for parent in parents {
+        while !parent.starts_with(&dir) {
+            dir = dir.parent()?.to_path_buf();
+        }
+    }
+
+    Some(dir.display().to_string())
+}
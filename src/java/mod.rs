@@ -1,61 +1,169 @@
-use std::{fs, os::unix::fs::PermissionsExt};
+use std::{fs, os::unix::fs::PermissionsExt, path::Path};
 
 use async_std::process::Command;
 
 pub mod arch;
 pub mod extract;
+pub mod metacache;
 pub mod download;
+pub mod runtime;
+pub mod system;
+pub mod verify;
 
-use crate::utils::metacache;
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    data::db::Database,
+    utils::{cancel::CancellationToken, download::progress::ProgressSender},
+};
+use runtime::JavaRuntime;
 
 const JAVA_VERSIONS_MANIFEST_URL: &str = "https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
 
+/// Runtime used by versions which don't declare the required Java
+const LEGACY_COMPONENT: &str = "jre-legacy";
+const LEGACY_MAJOR_VERSION: u64 = 8;
+
+/// Java runtime required by a version manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct JavaRequirement {
+    pub component: String,
+    pub major_version: String,
+}
+
+impl JavaRequirement {
+    /// Reads `javaVersion` of the official manifest or
+    /// `compatibleJavaName`/`compatibleJavaMajors` of the Prism one.
+    /// Versions without any of them run on Java 8
+    pub fn from_manifest(manifest: &serde_json::Value) -> Self {
+        let official = &manifest["javaVersion"];
+        if let (Some(component), Some(major)) = (
+            official["component"].as_str(),
+            official["majorVersion"].as_u64(),
+        ) {
+            return Self::new(component, major);
+        }
+
+        let major = manifest["compatibleJavaMajors"]
+            .as_array()
+            .and_then(|majors| majors.first())
+            .and_then(|major| major.as_u64());
+
+        match (manifest["compatibleJavaName"].as_str(), major) {
+            (Some(component), Some(major)) => Self::new(component, major),
+            (None, Some(major)) => Self::from_major_version(major),
+            _ => Self::new(LEGACY_COMPONENT, LEGACY_MAJOR_VERSION),
+        }
+    }
+
+    /// Picks the Mojang runtime component for the major Java version
+    pub fn from_major_version(major: u64) -> Self {
+        let component = match major {
+            0..=8 => LEGACY_COMPONENT,
+            9..=16 => "java-runtime-alpha",
+            17 => "java-runtime-gamma",
+            _ => "java-runtime-delta",
+        };
+
+        Self::new(component, major)
+    }
+
+    fn new(component: &str, major: u64) -> Self {
+        JavaRequirement {
+            component: component.to_string(),
+            major_version: major.to_string(),
+        }
+    }
+}
+
 
 #[derive(Eq, Hash, PartialEq, Debug)]
 pub struct EntryInfo {
     path: String,
     path_type: String,
+    hash: Option<String>,
 }
 
 pub struct Java {
     version: String,
+    version_name: String,
     runtime_name: String,
     manifest_url: String,
     destination: String,
     sha1: String,
+    progress: Option<ProgressSender>,
+    /// Use only the installed runtime
+    offline: bool,
 }
 
 impl Java {
     pub fn new(version: String, runtime_name: String, destination: String) -> Java {
         Java {
             version,
+            version_name: String::new(),
             runtime_name,
             manifest_url: JAVA_VERSIONS_MANIFEST_URL.to_string(),
             destination,
             sha1: String::from(""),
+            progress: None,
+            offline: false,
         }
     }
 
-    pub async fn init(mut self, metacache_path: String) -> Result<(), String> {
-        let mut metacache_value = match metacache::get_json(&metacache_path) {
-            Ok(value) => value,
-            Err(e) => return Err(e),
-        };
+    /// Reports received bytes of the runtime files
+    pub fn with_progress(mut self, sender: ProgressSender) -> Java {
+        self.progress = Some(sender);
+        self
+    }
+
+    pub fn with_offline(mut self, offline: bool) -> Java {
+        self.offline = offline;
+        self
+    }
 
-        let exec_path = extract::start_extraction(metacache_path, &mut metacache_value, &mut self).await.unwrap();
+    /// Installs the runtime if it is missing and retrieves the path to its executable
+    pub async fn init(mut self, db: &Database, cancel: &CancellationToken) -> Result<String, String> {
+        if let Some(exec_path) = self.find_installed(db).await {
+            println!("Java runtime {} is already installed", self.runtime_name);
+            return Ok(exec_path);
+        }
+
+        if self.offline {
+            return Err(format!(
+                "Java runtime {} is not installed and can't be downloaded offline",
+                self.runtime_name
+            ));
+        }
+
+        let exec_path = extract::start_extraction(db, &mut self, cancel).await?;
 
         if let Err(e) = Self::set_permissions(&exec_path) {
             return Err(e);
         }
 
-        let output = Command::new(exec_path)
-            .arg("--version")
-            .output()
-            .await.unwrap();
+        let output = match Command::new(&exec_path).arg("-version").output().await {
+            Ok(output) => output,
+            Err(e) => return Err(format!("Failed to run java {}: {}", exec_path, e)),
+        };
 
         println!("{:#?}", output);
 
-        Ok(())
+        Ok(exec_path)
+    }
+
+    async fn find_installed(&self, db: &Database) -> Option<String> {
+        let arch = arch::manifest_osarch()?;
+
+        match JavaRuntime::find(db, &self.runtime_name, arch).await {
+            Ok(runtime) => runtime
+                .map(|runtime| runtime.exec_path)
+                .filter(|exec_path| Path::new(exec_path).exists()),
+            Err(e) => {
+                eprintln!("Failed to find java runtime {}: {}", self.runtime_name, e);
+                None
+            }
+        }
     }
 
     fn set_permissions(exec_path: &String) -> Result<(), String> {
@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::data::db::{DBError, Database, Result};
+
+use super::{EntryInfo, Java};
+
+/// Java runtime installed by the launcher
+#[derive(Debug, Serialize, Clone)]
+pub struct JavaRuntime {
+    pub id: i64,
+    /// Mojang runtime component, e.g. `java-runtime-delta`
+    pub component: String,
+    pub version: String,
+    /// Platform key of the runtime manifest, e.g. `linux` or `mac-os-arm64`
+    pub arch: String,
+    /// Directory the runtime is installed to
+    pub path: String,
+    pub exec_path: String,
+    pub manifest_sha1: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct JavaFile {
+    pub path: String,
+    pub path_type: String,
+    /// sha1 of the raw file, `None` for directories and links
+    pub hash: Option<String>,
+}
+
+/// Runtime with all of its files
+#[derive(Debug, Serialize)]
+pub struct JavaRuntimeDetails {
+    #[serde(flatten)]
+    pub runtime: JavaRuntime,
+    pub files: Vec<JavaFile>,
+}
+
+impl JavaRuntime {
+    pub async fn list(db: &Database) -> Result<Vec<JavaRuntime>> {
+        let runtimes = sqlx::query_as!(
+            JavaRuntime,
+            r#"
+            SELECT id AS "id!", component, version, arch, path, exec_path, manifest_sha1
+            FROM javas
+            ORDER BY component
+            "#
+        )
+        .fetch_all(&db.pool)
+        .await?;
+
+        Ok(runtimes)
+    }
+
+    pub async fn get(db: &Database, id: i64) -> Result<JavaRuntimeDetails> {
+        let runtime = sqlx::query_as!(
+            JavaRuntime,
+            r#"
+            SELECT id AS "id!", component, version, arch, path, exec_path, manifest_sha1
+            FROM javas
+            WHERE id = ?1
+            "#,
+            id
+        )
+        .fetch_optional(&db.pool)
+        .await?
+        .ok_or_else(|| DBError::NotFound(format!("Java runtime {id} not found")))?;
+
+        let files = Self::files(db, id).await?;
+
+        Ok(JavaRuntimeDetails { runtime, files })
+    }
+
+    /// Find the runtime of the component installed for the platform
+    pub async fn find(db: &Database, component: &str, arch: &str) -> Result<Option<JavaRuntime>> {
+        let runtime = sqlx::query_as!(
+            JavaRuntime,
+            r#"
+            SELECT id AS "id!", component, version, arch, path, exec_path, manifest_sha1
+            FROM javas
+            WHERE component = ?1 AND arch = ?2
+            "#,
+            component,
+            arch
+        )
+        .fetch_optional(&db.pool)
+        .await?;
+
+        Ok(runtime)
+    }
+
+    pub async fn files(db: &Database, id: i64) -> Result<Vec<JavaFile>> {
+        let files = sqlx::query_as!(
+            JavaFile,
+            r#"
+            SELECT path, path_type, hash
+            FROM java_files
+            WHERE java_id = ?1
+            ORDER BY path
+            "#,
+            id
+        )
+        .fetch_all(&db.pool)
+        .await?;
+
+        Ok(files)
+    }
+
+    /// Remove the runtime from DB and retrieve it,
+    /// so the caller can clean up its directory
+    pub async fn delete(db: &Database, id: i64) -> Result<JavaRuntime> {
+        // Files of the runtime are removed by `ON DELETE CASCADE`
+        sqlx::query_as!(
+            JavaRuntime,
+            r#"
+            DELETE FROM javas
+            WHERE id = ?1
+            RETURNING id AS "id!", component, version, arch, path, exec_path, manifest_sha1
+            "#,
+            id
+        )
+        .fetch_optional(&db.pool)
+        .await?
+        .ok_or_else(|| DBError::NotFound(format!("Java runtime {id} not found")))
+    }
+
+    /// Register the runtime and its downloaded files.
+    /// Files of the same runtime installed earlier are kept,
+    /// so an interrupted installation can be continued
+    pub async fn register(
+        db: &Database,
+        java: &Java,
+        arch: &str,
+        exec_path: &str,
+        entries: &HashSet<EntryInfo>,
+    ) -> Result<i64> {
+        let mut tx = db.pool.begin().await?;
+
+        let previous = sqlx::query!(
+            "SELECT manifest_sha1 FROM javas WHERE component = ?1 AND arch = ?2",
+            java.runtime_name,
+            arch
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let rec = sqlx::query!(
+            r#"
+            INSERT INTO javas (component, version, arch, path, exec_path, manifest_sha1)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT (component, arch) DO UPDATE SET
+                version = excluded.version,
+                path = excluded.path,
+                exec_path = excluded.exec_path,
+                manifest_sha1 = excluded.manifest_sha1
+            RETURNING id
+            "#,
+            java.runtime_name,
+            java.version_name,
+            arch,
+            java.destination,
+            exec_path,
+            java.sha1
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // Files of the previous build of the runtime are overwritten
+        if previous.is_some_and(|p| p.manifest_sha1 != java.sha1) {
+            sqlx::query!("DELETE FROM java_files WHERE java_id = ?1", rec.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for entry in entries {
+            sqlx::query!(
+                r#"
+                INSERT INTO java_files (java_id, path, path_type, hash)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT (java_id, path) DO UPDATE SET
+                    path_type = excluded.path_type,
+                    hash = excluded.hash
+                "#,
+                rec.id,
+                entry.path,
+                entry.path_type,
+                entry.hash
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        println!("Registered java runtime: {}", rec.id);
+        Ok(rec.id)
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use async_std::{future::timeout, process::Command};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    data::{
+        db::{DBError, Database, Result},
+        GlobalDataState,
+    },
+    utils::cancel::CancellationToken,
+    websocket::messages::task::TaskProgress,
+};
+
+#[cfg(windows)]
+const JAVA_EXECUTABLE: &str = "java.exe";
+#[cfg(not(windows))]
+const JAVA_EXECUTABLE: &str = "java";
+
+/// Directories which keep Java installations in their subdirectories
+const JVM_DIRS: &[&str] = &[
+    "/usr/lib/jvm",
+    "/usr/lib64/jvm",
+    "/usr/java",
+    "/opt/java",
+    "/opt/jdk",
+    "/Library/Java/JavaVirtualMachines",
+    "C:\\Program Files\\Java",
+    "C:\\Program Files\\Eclipse Adoptium",
+];
+
+/// Same as `JVM_DIRS`, but relative to the user home
+const HOME_JVM_DIRS: &[&str] = &[
+    ".sdkman/candidates/java",
+    ".jdks",
+    "Library/Java/JavaVirtualMachines",
+];
+
+/// Broken installations may hang instead of printing the properties
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Error)]
+pub enum SystemJavaError {
+    #[error("Failed to register java: {0}")]
+    Db(#[from] DBError),
+
+    #[error("Scan was cancelled")]
+    Cancelled,
+}
+
+/// Java installation found on the system
+#[derive(Debug, Serialize, Clone)]
+pub struct SystemJava {
+    pub id: i64,
+    pub vendor: String,
+    pub version: String,
+    pub major_version: i64,
+    pub arch: String,
+    /// `java.home` of the installation
+    pub path: String,
+    pub exec_path: String,
+}
+
+/// Properties printed by `java -XshowSettings:properties -version`
+#[derive(Debug, PartialEq)]
+pub struct JavaProperties {
+    pub vendor: String,
+    pub version: String,
+    pub arch: String,
+    pub home: String,
+}
+
+impl JavaProperties {
+    pub fn parse(output: &str) -> Option<Self> {
+        // Lines look like `    java.version = 17.0.9`,
+        // values of list properties continue on the next lines without a key
+        let properties: HashMap<&str, &str> = output
+            .lines()
+            .filter_map(|line| line.split_once(" = "))
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .collect();
+
+        Some(JavaProperties {
+            vendor: properties.get("java.vendor")?.to_string(),
+            version: properties.get("java.version")?.to_string(),
+            arch: properties.get("os.arch")?.to_string(),
+            home: properties.get("java.home")?.to_string(),
+        })
+    }
+
+    /// `1.8.0_392` is Java 8 and `17.0.9` is Java 17
+    pub fn major_version(&self) -> Option<i64> {
+        let mut parts = self.version.split(|c: char| !c.is_ascii_digit());
+        let major = parts.next()?.parse::<i64>().ok()?;
+
+        if major == 1 {
+            parts.next()?.parse().ok()
+        } else {
+            Some(major)
+        }
+    }
+}
+
+impl SystemJava {
+    /// Look for Java installations and register them.
+    /// Installations which are not found anymore are removed
+    pub async fn scan<'a>(
+        state: &GlobalDataState<'a>,
+        task_id: usize,
+        cancel: &CancellationToken,
+    ) -> std::result::Result<Vec<SystemJava>, SystemJavaError> {
+        let db = &state.static_data.db;
+
+        // Runtimes downloaded by the launcher are registered separately
+        let managed_dir = state.static_data.launcher_root_path.join("java");
+        let managed_dir = managed_dir.canonicalize().unwrap_or(managed_dir);
+        let candidates: Vec<PathBuf> = find_candidates()
+            .into_iter()
+            .filter(|exec_path| !exec_path.starts_with(&managed_dir))
+            .collect();
+
+        let total = candidates.len();
+        let mut homes = HashSet::new();
+        let mut found = Vec::new();
+
+        for (i, exec_path) in candidates.iter().enumerate() {
+            if cancel.is_cancelled() {
+                return Err(SystemJavaError::Cancelled);
+            }
+
+            // `/usr/bin/java` on macOS is a stub, which runs one of the installations
+            if let Some(properties) = probe(exec_path).await {
+                if homes.insert(properties.home.clone()) {
+                    if let Some(java) = Self::register(db, &properties, exec_path).await? {
+                        found.push(java);
+                    }
+                }
+            }
+
+            let progress = TaskProgress::Determinable {
+                current: Some(i + 1),
+                total: Some(total),
+            };
+            if let Err(e) = state.update_task(task_id, |t| t.progress = progress).await {
+                eprintln!("Failed to update java scan progress: {}", e);
+            }
+        }
+
+        Self::remove_missing(db, &found).await?;
+
+        Ok(found)
+    }
+
+    pub async fn get(db: &Database, id: i64) -> Result<SystemJava> {
+        sqlx::query_as!(
+            SystemJava,
+            r#"
+            SELECT id AS "id!", vendor, version, major_version, arch, path, exec_path
+            FROM system_javas
+            WHERE id = ?1
+            "#,
+            id
+        )
+        .fetch_optional(&db.pool)
+        .await?
+        .ok_or_else(|| DBError::NotFound(format!("System java {id} not found")))
+    }
+
+    pub async fn list(db: &Database) -> Result<Vec<SystemJava>> {
+        let javas = sqlx::query_as!(
+            SystemJava,
+            r#"
+            SELECT id AS "id!", vendor, version, major_version, arch, path, exec_path
+            FROM system_javas
+            ORDER BY major_version DESC
+            "#
+        )
+        .fetch_all(&db.pool)
+        .await?;
+
+        Ok(javas)
+    }
+
+    async fn register(
+        db: &Database,
+        properties: &JavaProperties,
+        exec_path: &Path,
+    ) -> Result<Option<SystemJava>> {
+        let Some(major_version) = properties.major_version() else {
+            eprintln!("Unknown java version {} of {}", properties.version, exec_path.display());
+            return Ok(None);
+        };
+        let exec_path = exec_path.display().to_string();
+
+        let java = sqlx::query_as!(
+            SystemJava,
+            r#"
+            INSERT INTO system_javas (vendor, version, major_version, arch, path, exec_path)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT (exec_path) DO UPDATE SET
+                vendor = excluded.vendor,
+                version = excluded.version,
+                major_version = excluded.major_version,
+                arch = excluded.arch,
+                path = excluded.path
+            RETURNING id AS "id!", vendor, version, major_version, arch, path, exec_path
+            "#,
+            properties.vendor,
+            properties.version,
+            major_version,
+            properties.arch,
+            properties.home,
+            exec_path
+        )
+        .fetch_one(&db.pool)
+        .await?;
+
+        println!("Registered system java: {} {}", java.vendor, java.exec_path);
+        Ok(Some(java))
+    }
+
+    async fn remove_missing(db: &Database, found: &[SystemJava]) -> Result<()> {
+        let found_ids: HashSet<i64> = found.iter().map(|java| java.id).collect();
+
+        for java in Self::list(db).await? {
+            if found_ids.contains(&java.id) {
+                continue;
+            }
+
+            sqlx::query!("DELETE FROM system_javas WHERE id = ?1", java.id)
+                .execute(&db.pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Retrieve java executables from `JAVA_HOME`, `PATH`
+/// and well-known installation directories
+fn find_candidates() -> Vec<PathBuf> {
+    let mut exec_paths = Vec::new();
+
+    if let Some(java_home) = env::var_os("JAVA_HOME") {
+        exec_paths.push(PathBuf::from(java_home).join("bin").join(JAVA_EXECUTABLE));
+    }
+
+    if let Some(path) = env::var_os("PATH") {
+        for dir in env::split_paths(&path) {
+            exec_paths.push(dir.join(JAVA_EXECUTABLE));
+        }
+    }
+
+    let mut jvm_dirs: Vec<PathBuf> = JVM_DIRS.iter().map(PathBuf::from).collect();
+    if let Some(home) = home::home_dir() {
+        jvm_dirs.extend(HOME_JVM_DIRS.iter().map(|dir| home.join(dir)));
+    }
+
+    for dir in jvm_dirs {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            // macOS bundles keep the installation in `Contents/Home`
+            let bundle_home = entry.path().join("Contents").join("Home");
+            let java_home = if bundle_home.is_dir() {
+                bundle_home
+            } else {
+                entry.path()
+            };
+
+            exec_paths.push(java_home.join("bin").join(JAVA_EXECUTABLE));
+        }
+    }
+
+    // Symlinks such as `/usr/bin/java` or SDKMAN `current`
+    // point to the installations found in other places
+    let mut seen = HashSet::new();
+    exec_paths
+        .into_iter()
+        .filter_map(|exec_path| exec_path.canonicalize().ok())
+        .filter(|exec_path| exec_path.is_file())
+        .filter(|exec_path| seen.insert(exec_path.clone()))
+        .collect()
+}
+
+async fn probe(exec_path: &Path) -> Option<JavaProperties> {
+    let output = Command::new(exec_path)
+        .arg("-XshowSettings:properties")
+        .arg("-version")
+        .kill_on_drop(true)
+        .output();
+
+    let output = match timeout(PROBE_TIMEOUT, output).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => {
+            eprintln!("Failed to run {}: {}", exec_path.display(), e);
+            return None;
+        }
+        Err(_) => {
+            eprintln!("{} did not respond in time", exec_path.display());
+            return None;
+        }
+    };
+
+    // Settings are printed to stderr
+    JavaProperties::parse(&String::from_utf8_lossy(&output.stderr))
+}
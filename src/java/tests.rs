@@ -0,0 +1,156 @@
+use serde_json::json;
+
+use super::*;
+
+#[test]
+fn requirement_from_official_manifest() {
+    // javaVersion of 1.20.6
+    let manifest = json!({
+        "id": "1.20.6",
+        "javaVersion": { "component": "java-runtime-delta", "majorVersion": 21 }
+    });
+
+    assert_eq!(
+        JavaRequirement::from_manifest(&manifest),
+        JavaRequirement::new("java-runtime-delta", 21)
+    );
+}
+
+#[test]
+fn requirement_from_prism_manifest() {
+    let manifest = json!({
+        "version": "1.18.2",
+        "compatibleJavaMajors": [17],
+        "compatibleJavaName": "java-runtime-gamma"
+    });
+
+    assert_eq!(
+        JavaRequirement::from_manifest(&manifest),
+        JavaRequirement::new("java-runtime-gamma", 17)
+    );
+}
+
+#[test]
+fn requirement_from_prism_majors_only() {
+    let manifest = json!({ "version": "1.17.1", "compatibleJavaMajors": [16, 17] });
+
+    assert_eq!(
+        JavaRequirement::from_manifest(&manifest),
+        JavaRequirement::new("java-runtime-alpha", 16)
+    );
+}
+
+#[test]
+fn requirement_defaults_to_legacy() {
+    // Old manifests don't declare java at all
+    let manifest = json!({ "id": "1.6.4" });
+
+    assert_eq!(
+        JavaRequirement::from_manifest(&manifest),
+        JavaRequirement::new("jre-legacy", 8)
+    );
+}
+
+// Trimmed output of `java -XshowSettings:properties -version`
+const TEMURIN_PROPERTIES: &str = r#"Property settings:
+    file.encoding = UTF-8
+    java.home = /usr/lib/jvm/temurin-17-jdk-amd64
+    java.library.path = /usr/java/packages/lib
+        /usr/lib64
+        /lib64
+    java.vendor = Eclipse Adoptium
+    java.version = 17.0.9
+    os.arch = amd64
+    os.name = Linux
+
+openjdk version "17.0.9" 2023-10-17
+"#;
+
+#[test]
+fn parse_java_properties() {
+    let properties = system::JavaProperties::parse(TEMURIN_PROPERTIES).unwrap();
+
+    assert_eq!(
+        properties,
+        system::JavaProperties {
+            vendor: "Eclipse Adoptium".to_string(),
+            version: "17.0.9".to_string(),
+            arch: "amd64".to_string(),
+            home: "/usr/lib/jvm/temurin-17-jdk-amd64".to_string(),
+        }
+    );
+    assert_eq!(properties.major_version(), Some(17));
+}
+
+#[test]
+fn parse_java_properties_without_settings() {
+    // Java which doesn't support -XshowSettings prints only the version
+    assert_eq!(system::JavaProperties::parse("java version \"1.6.0_45\""), None);
+}
+
+#[test]
+fn legacy_java_major_version() {
+    let properties = system::JavaProperties {
+        vendor: "Oracle Corporation".to_string(),
+        version: "1.8.0_392".to_string(),
+        arch: "amd64".to_string(),
+        home: "/usr/lib/jvm/java-8-openjdk/jre".to_string(),
+    };
+
+    assert_eq!(properties.major_version(), Some(8));
+}
+
+#[test]
+fn legacy_runtimes_from_metacache() {
+    let metacache = json!({
+        "javas": [
+            {
+                "runtime-type": "java-runtime-gamma",
+                "version": "17",
+                "exec_path": "/home/user/.sonata/java/bin/java",
+                "sha1": "4f4ee9ab4fd5e1d3e8d1e3bb8a1b1b0a1e47a1b5",
+                "paths": [
+                    { "path": "/home/user/.sonata/java/bin", "path_type": "directory" },
+                    { "path": "/home/user/.sonata/java/bin/java", "path_type": "file" },
+                    { "path": "/home/user/.sonata/java/lib/modules", "path_type": "file" }
+                ]
+            },
+            // Entries without the executable can't be used
+            { "runtime-type": "jre-legacy", "version": "8" }
+        ],
+        "libraries": [],
+        "assets": []
+    });
+
+    let runtimes = metacache::legacy_runtimes(&metacache);
+
+    assert_eq!(runtimes.len(), 1);
+    assert_eq!(runtimes[0].component, "java-runtime-gamma");
+    assert_eq!(runtimes[0].version, "17");
+    // Runtimes were installed right into the java dir, not into the component one
+    assert_eq!(runtimes[0].path, "/home/user/.sonata/java");
+    assert!(runtimes[0].entries.contains(&EntryInfo {
+        path: "/home/user/.sonata/java/bin/java".to_string(),
+        path_type: "file".to_string(),
+        hash: None,
+    }));
+    assert_eq!(runtimes[0].entries.len(), 3);
+}
+
+#[test]
+fn legacy_runtime_dir_on_macos() {
+    let metacache = json!({
+        "javas": [{
+            "runtime-type": "java-runtime-delta",
+            "exec_path": "/Users/user/.sonata/java/jre.bundle/Contents/Home/bin/java",
+            "paths": [
+                { "path": "/Users/user/.sonata/java/jre.bundle", "path_type": "directory" },
+                { "path": "/Users/user/.sonata/java/jre.bundle/Contents/Home/bin/java", "path_type": "file" }
+            ]
+        }]
+    });
+
+    let runtimes = metacache::legacy_runtimes(&metacache);
+
+    assert_eq!(runtimes[0].path, "/Users/user/.sonata/java");
+}
@@ -0,0 +1,202 @@
+use std::{collections::HashSet, fs, os::unix::fs::PermissionsExt, path::Path};
+
+use async_std::fs::create_dir_all;
+use serde::Serialize;
+
+use super::{download, extract, runtime::JavaRuntime, EntryInfo, Java};
+use crate::{
+    data::db::Database,
+    instance::websocket::{OperationWsExt, OperationWsMessageLocked},
+    utils::{
+        cancel::CancellationToken,
+        download::{file_sha1, progress::ProgressSender},
+    },
+    websocket::messages::operation::{
+        process::{FileStatus, ProcessTarget},
+        stage::{OperationStage, StageStatus},
+    },
+};
+
+#[derive(Debug, Default, Serialize)]
+pub struct VerifyReport {
+    pub verified: usize,
+    pub repaired: usize,
+    /// Paths of the files which could not be restored
+    pub failed: Vec<String>,
+}
+
+impl Java {
+    /// Describe the installed runtime to check it against the current manifest
+    pub fn from_runtime(runtime: &JavaRuntime) -> Java {
+        let major_version = runtime
+            .version
+            .split(|c: char| !c.is_ascii_digit())
+            .next()
+            .unwrap_or_default();
+
+        Java::new(
+            major_version.to_string(),
+            runtime.component.clone(),
+            runtime.path.clone(),
+        )
+    }
+
+    /// Re-hash every file of the runtime against the runtime manifest,
+    /// redownload missing or corrupted files and recreate links
+    pub async fn verify<'a>(
+        mut self,
+        db: &Database,
+        exec_path: &str,
+        ws_status: OperationWsMessageLocked<'a>,
+        cancel: &CancellationToken,
+    ) -> Result<VerifyReport, String> {
+        let manifest = match extract::parse_main_manifest(db, &mut self).await? {
+            (Some((manifest, _)), _) => manifest,
+            _ => return Err("Failed to retrieve the runtime manifest".to_string()),
+        };
+
+        let files = match manifest["files"].as_object() {
+            Some(files) => files,
+            None => return Err("Runtime manifest doesn't contain files".to_string()),
+        };
+
+        let total = files.len();
+        ws_status
+            .clone()
+            .start_stage_determinable(OperationStage::VerifyFiles, None, 0, total)
+            .await;
+
+        let mut report = VerifyReport::default();
+        let mut entries = HashSet::new();
+
+        for (current, (raw_path, file)) in files.iter().enumerate() {
+            if cancel.is_cancelled() {
+                return Err("Java verification was cancelled".to_string());
+            }
+
+            let path = format!("{}/{}", self.destination, raw_path);
+            let path_type = file["type"].as_str().unwrap_or_default();
+
+            let result = match path_type {
+                "directory" => verify_directory(&path).await,
+                "link" => verify_link(&path, file["target"].as_str()),
+                "file" => verify_file(&path, file, cancel, self.progress.as_ref()).await,
+                _ => Err(format!("Unsupported file type {}", path_type)),
+            };
+
+            let status = match result {
+                Ok(status) => {
+                    match status {
+                        FileStatus::Repaired => report.repaired += 1,
+                        _ => report.verified += 1,
+                    }
+
+                    entries.insert(EntryInfo {
+                        path: path.clone(),
+                        path_type: path_type.to_string(),
+                        hash: file["downloads"]["raw"]["sha1"].as_str().map(String::from),
+                    });
+                    status
+                }
+                Err(e) => {
+                    eprintln!("Failed to restore {}: {}", path, e);
+                    report.failed.push(path);
+                    FileStatus::FailedToDownload
+                }
+            };
+
+            ws_status
+                .clone()
+                .update_determinable(
+                    OperationStage::VerifyFiles,
+                    Some(ProcessTarget::file(raw_path.clone(), status)),
+                    current + 1,
+                    total,
+                )
+                .await;
+        }
+
+        // Manifest could be updated since the installation, so hashes are refreshed
+        let arch = super::arch::manifest_osarch().unwrap_or_default();
+        if let Err(e) = JavaRuntime::register(db, &self, arch, exec_path, &entries).await {
+            return Err(format!("Failed to register java runtime: {e}"));
+        }
+
+        let stage_status = if report.failed.is_empty() {
+            StageStatus::Completed
+        } else {
+            StageStatus::Failed
+        };
+        ws_status
+            .clone()
+            .complete_stage(stage_status, OperationStage::VerifyFiles, 0.0, None)
+            .await;
+
+        Ok(report)
+    }
+}
+
+async fn verify_directory(path: &str) -> Result<FileStatus, String> {
+    if Path::new(path).is_dir() {
+        return Ok(FileStatus::Verified);
+    }
+
+    create_dir_all(path).await.map_err(|e| e.to_string())?;
+    Ok(FileStatus::Repaired)
+}
+
+fn verify_link(path: &str, target: Option<&str>) -> Result<FileStatus, String> {
+    let target = match target {
+        Some(target) => target,
+        None => return Err("Link doesn't have a target".to_string()),
+    };
+
+    match fs::read_link(path) {
+        Ok(current) if current == Path::new(target) => Ok(FileStatus::Verified),
+        _ => {
+            download::create_link(path, target)?;
+            Ok(FileStatus::Repaired)
+        }
+    }
+}
+
+async fn verify_file(
+    path: &str,
+    file: &serde_json::Value,
+    cancel: &CancellationToken,
+    progress: Option<&ProgressSender>,
+) -> Result<FileStatus, String> {
+    let expected = match file["downloads"]["raw"]["sha1"].as_str() {
+        Some(sha1) => sha1,
+        None => return Err("File doesn't have a hash".to_string()),
+    };
+    let executable = file["executable"].as_bool().unwrap_or(false);
+
+    let mut status = FileStatus::Verified;
+    if file_sha1(Path::new(path)).await.ok().as_deref() != Some(expected) {
+        let downloads = match file["downloads"].as_object() {
+            Some(downloads) => downloads.to_owned(),
+            None => return Err("File doesn't have downloads".to_string()),
+        };
+
+        download::get_java_part(downloads, "file", &path.to_string(), cancel, progress).await?;
+
+        let sha1 = file_sha1(Path::new(path)).await.map_err(|e| e.to_string())?;
+        if sha1 != expected {
+            return Err(format!("SHA1 mismatch at {}", path));
+        }
+
+        status = FileStatus::Repaired;
+    }
+
+    if executable {
+        let mut permissions = fs::metadata(path).map_err(|e| e.to_string())?.permissions();
+        if permissions.mode() & 0o111 == 0 {
+            permissions.set_mode(0o775);
+            fs::set_permissions(path, permissions).map_err(|e| e.to_string())?;
+            status = FileStatus::Repaired;
+        }
+    }
+
+    Ok(status)
+}
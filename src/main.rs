@@ -1,9 +1,18 @@
 use data::GlobalDataState;
 use endpoints::{
-    debug_ws, handle_init_root,
-    instance::{init_instance_ws, instance_options_dispatcher, run_instance_ws},
-    java::download_java_ws,
-    versions::{get_version_ws, get_versions},
+    debug_ws,
+    download::{
+        clear_cache, get_cache_settings, get_download_limits, get_mirrors, get_retry_policy,
+        set_cache_settings, set_download_limits, set_mirrors, set_retry_policy,
+    },
+    handle_init_root,
+    instance::{
+        init_instance_ws, instance_options_dispatcher, run_instance_ws, verify_instance_ws,
+    },
+    java::{
+        delete_java, download_java_ws, get_java, list_javas, list_system_javas, verify_java_ws,
+    },
+    versions::{get_version_ws, get_versions, get_versions_catalog},
 };
 
 use http_types::headers::HeaderValue;
@@ -13,7 +22,11 @@ use tide::{security::Origin, Request};
 use tide_websockets::{Message, WebSocket, WebSocketConnection};
 
 use crate::endpoints::{
-    instance::{instance_dispather, instance_option_change, instance_options_sync},
+    instance::{
+        instance_console_ws, instance_dispather, instance_option_change, instance_options_sync,
+        stop_instance,
+    },
+    task::{cancel_task, requested_task_ws},
     versions::get_versions_unified,
 };
 
@@ -40,7 +53,7 @@ async fn main() -> tide::Result<()> {
     app.with(
         CorsMiddleware::new()
             .allow_origin(Origin::from("*"))
-            .allow_methods("GET, POST".parse::<HeaderValue>().unwrap()),
+            .allow_methods("GET, POST, DELETE".parse::<HeaderValue>().unwrap()),
     );
 
     // Init routes
@@ -48,21 +61,49 @@ async fn main() -> tide::Result<()> {
 
     // Java routes
     app.at("/ws/java/install")
-        .get(WebSocket::new(|_req, ws| download_java_ws(ws)));
+        .get(WebSocket::new(|req, ws| download_java_ws(req, ws)));
+    app.at("/ws/java/verify")
+        .get(WebSocket::new(|req, ws| verify_java_ws(req, ws)));
+    app.at("/java").get(list_javas);
+    app.at("/java/system").get(list_system_javas);
+    app.at("/java/:id").get(get_java).delete(delete_java);
+
+    // Download routes
+    app.at("/download/limits")
+        .get(get_download_limits)
+        .post(set_download_limits);
+    app.at("/download/mirrors")
+        .get(get_mirrors)
+        .post(set_mirrors);
+    app.at("/download/retry")
+        .get(get_retry_policy)
+        .post(set_retry_policy);
+    app.at("/download/cache")
+        .get(get_cache_settings)
+        .post(set_cache_settings)
+        .delete(clear_cache);
 
     // Instance routes
     app.at("/instance/download_versions").post(get_versions);
     app.at("/instance/download_versions_unified")
         .get(get_versions_unified);
+    app.at("/instance/versions/catalog")
+        .get(get_versions_catalog);
     app.at("/ws/instance/get_version")
         .get(WebSocket::new(|_req, ws| get_version_ws(ws)));
 
     app.at("/ws/instance/init")
         .get(WebSocket::new(|req, ws| init_instance_ws(req, ws)));
+    app.at("/ws/instance/verify")
+        .get(WebSocket::new(|req, ws| verify_instance_ws(req, ws)));
     app.at("/ws/instance/run")
         .get(WebSocket::new(|req, ws| run_instance_ws(req, ws)));
     app.at("/ws/instance/list")
         .get(WebSocket::new(|req, ws| instance_dispather(req, ws)));
+    app.at("/ws/instance/console")
+        .get(WebSocket::new(|req, ws| instance_console_ws(req, ws)));
+    app.at("/instance/:id/stop")
+        .post(stop_instance);
     app.at("/instance/:id/:page")
         .get(instance_options_dispatcher);
     app.at("/instance/options/sync")
@@ -71,6 +112,12 @@ async fn main() -> tide::Result<()> {
         .post(instance_option_change);
     // app.at("/instance/options").get(instance_options_dispatcher);
 
+    // Task routes
+    app.at("/ws/task/request")
+        .get(WebSocket::new(|req, ws| requested_task_ws(req, ws)));
+    app.at("/task/:id/cancel")
+        .post(cancel_task);
+
     app.at("/debug/ws")
         .get(WebSocket::new(|_req, stream| debug_ws(stream)));
     app.at("/debug/tasks/notif")
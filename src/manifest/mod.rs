@@ -1,5 +1,7 @@
 use crate::utils::download::download_in_json;
 
+pub mod rules;
+
 const GLOBAL_MANIFEST_URL: &'static str = "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
 
 pub async fn get_global_manifest() -> Result<serde_json::Value, String> {
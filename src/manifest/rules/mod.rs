@@ -0,0 +1,143 @@
+use std::collections::HashSet;
+
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use strum::Display;
+
+#[cfg(test)]
+mod tests;
+
+/// Features which can be requested by `features` rules of the manifest arguments
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Display, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum LaunchFeature {
+    IsDemoUser,
+    HasCustomResolution,
+    HasQuickPlaysSupport,
+    IsQuickPlaySingleplayer,
+    IsQuickPlayMultiplayer,
+    IsQuickPlayRealms,
+}
+
+/// Environment which manifest rules are evaluated against
+#[derive(Debug, Clone)]
+pub struct RuleContext {
+    os_name: String,
+    os_arch: String,
+    os_version: String,
+    features: HashSet<String>,
+}
+
+impl RuleContext {
+    /// Context of the current machine with no features enabled
+    pub fn current() -> Self {
+        Self::new(
+            current_os_name().to_string(),
+            current_os_arch().to_string(),
+            os_info::get().version().to_string(),
+        )
+    }
+
+    pub fn new<S: Into<String>>(os_name: S, os_arch: S, os_version: S) -> Self {
+        RuleContext {
+            os_name: os_name.into(),
+            os_arch: os_arch.into(),
+            os_version: os_version.into(),
+            features: HashSet::new(),
+        }
+    }
+
+    /// Overrides the OS name rules are matched against.
+    /// Prism meta names platforms like natives classifiers, e.g. `osx-arm64`
+    pub fn with_os_name<S: Into<String>>(mut self, os_name: S) -> Self {
+        self.os_name = os_name.into();
+        self
+    }
+
+    pub fn with_features<'a, I>(mut self, features: I) -> Self
+    where
+        I: IntoIterator<Item = &'a LaunchFeature>,
+    {
+        self.features = features.into_iter().map(|f| f.to_string()).collect();
+        self
+    }
+
+    /// Evaluates `rules` array of a manifest entry (library or argument).
+    /// Entry without rules is always allowed, otherwise
+    /// the action of the last matched rule wins, like in the official launcher
+    pub fn allows(&self, rules: Option<&Vec<Value>>) -> bool {
+        let rules = match rules {
+            Some(rules) => rules,
+            None => return true,
+        };
+
+        let mut allowed = false;
+        for rule in rules {
+            if self.rule_matches(rule) {
+                allowed = rule.get("action").and_then(|v| v.as_str()) == Some("allow");
+            }
+        }
+
+        allowed
+    }
+
+    fn rule_matches(&self, rule: &Value) -> bool {
+        if let Some(os) = rule.get("os") {
+            if let Some(name) = os.get("name").and_then(|v| v.as_str()) {
+                if name != self.os_name {
+                    return false;
+                }
+            }
+
+            if let Some(arch) = os.get("arch").and_then(|v| v.as_str()) {
+                if arch != self.os_arch {
+                    return false;
+                }
+            }
+
+            if let Some(version) = os.get("version").and_then(|v| v.as_str()) {
+                match Regex::new(version) {
+                    Ok(re) if re.is_match(&self.os_version) => (),
+                    _ => return false,
+                }
+            }
+        }
+
+        if let Some(features) = rule.get("features").and_then(|v| v.as_object()) {
+            for (feature, expected) in features {
+                let enabled = self.features.contains(feature);
+                if expected.as_bool() != Some(enabled) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// OS name in terms of manifest rules
+fn current_os_name() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "windows"
+    } else if cfg!(target_os = "macos") {
+        "osx"
+    } else {
+        "linux"
+    }
+}
+
+/// CPU architecture in terms of manifest rules
+fn current_os_arch() -> &'static str {
+    if cfg!(target_arch = "x86") {
+        "x86"
+    } else if cfg!(target_arch = "aarch64") {
+        "arm64"
+    } else if cfg!(target_arch = "arm") {
+        "arm32"
+    } else {
+        "x86_64"
+    }
+}
@@ -0,0 +1,119 @@
+use serde_json::{json, Value};
+
+use super::*;
+
+struct Case {
+    name: &'static str,
+    // Manifest entry with `rules` field
+    entry: Value,
+    os: (&'static str, &'static str, &'static str),
+    features: &'static [LaunchFeature],
+    expected: bool,
+}
+
+fn cases() -> Vec<Case> {
+    // LWJGL 2 natives of 1.12.2, not used on macOS
+    let lwjgl_platform = json!({
+        "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.2-nightly-20140822",
+        "rules": [
+            { "action": "allow" },
+            { "action": "disallow", "os": { "name": "osx" } }
+        ]
+    });
+
+    // Replacement of the library above, used on macOS only
+    let lwjgl_platform_osx = json!({
+        "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.4-nightly-20150209",
+        "rules": [
+            { "action": "allow", "os": { "name": "osx" } }
+        ]
+    });
+
+    // Twitch natives of 1.8.9, disallowed on old macOS versions
+    let twitch_platform = json!({
+        "name": "tv.twitch:twitch-platform:6.5",
+        "rules": [
+            { "action": "allow" },
+            { "action": "disallow", "os": { "name": "osx", "version": "^10\\.5\\.\\d$" } }
+        ]
+    });
+
+    // JVM args of 1.20.1
+    let windows_10_args = json!({
+        "rules": [
+            { "action": "allow", "os": { "name": "windows", "version": "^10\\." } }
+        ],
+        "value": ["-Dos.name=Windows 10", "-Dos.version=10.0"]
+    });
+    let x86_stack_size = json!({
+        "rules": [{ "action": "allow", "os": { "arch": "x86" } }],
+        "value": "-Xss1M"
+    });
+
+    // Game args of 1.20.1
+    let demo = json!({
+        "rules": [{ "action": "allow", "features": { "is_demo_user": true } }],
+        "value": "--demo"
+    });
+    let quick_play = json!({
+        "rules": [{ "action": "allow", "features": { "has_quick_plays_support": true } }],
+        "value": ["--quickPlayPath", "${quickPlayPath}"]
+    });
+
+    // Prism meta names platforms like natives classifiers
+    let prism_lwjgl_arm = json!({
+        "name": "org.lwjgl:lwjgl:3.3.1",
+        "rules": [{ "action": "allow", "os": { "name": "osx-arm64" } }]
+    });
+
+    let linux = ("linux", "x86_64", "6.8.0");
+    let osx = ("osx", "arm64", "14.2.1");
+    let old_osx = ("osx", "x86_64", "10.5.8");
+    let windows_10 = ("windows", "x86_64", "10.0.19045");
+    let windows_7 = ("windows", "x86", "6.1.7601");
+
+    vec![
+        Case { name: "no rules", entry: json!({ "name": "com.mojang:brigadier:1.1.8" }), os: linux, features: &[], expected: true },
+        Case { name: "lwjgl platform on linux", entry: lwjgl_platform.clone(), os: linux, features: &[], expected: true },
+        Case { name: "lwjgl platform on osx", entry: lwjgl_platform, os: osx, features: &[], expected: false },
+        Case { name: "osx lwjgl on osx", entry: lwjgl_platform_osx.clone(), os: osx, features: &[], expected: true },
+        Case { name: "osx lwjgl on windows", entry: lwjgl_platform_osx, os: windows_10, features: &[], expected: false },
+        Case { name: "twitch on new osx", entry: twitch_platform.clone(), os: osx, features: &[], expected: true },
+        Case { name: "twitch on old osx", entry: twitch_platform, os: old_osx, features: &[], expected: false },
+        Case { name: "windows 10 args on windows 10", entry: windows_10_args.clone(), os: windows_10, features: &[], expected: true },
+        Case { name: "windows 10 args on windows 7", entry: windows_10_args, os: windows_7, features: &[], expected: false },
+        Case { name: "stack size on x86", entry: x86_stack_size.clone(), os: windows_7, features: &[], expected: true },
+        Case { name: "stack size on x86_64", entry: x86_stack_size, os: linux, features: &[], expected: false },
+        Case { name: "demo without feature", entry: demo.clone(), os: linux, features: &[], expected: false },
+        Case { name: "demo with feature", entry: demo, os: linux, features: &[LaunchFeature::IsDemoUser], expected: true },
+        Case { name: "quick play with other feature", entry: quick_play.clone(), os: linux, features: &[LaunchFeature::IsDemoUser], expected: false },
+        Case { name: "quick play with feature", entry: quick_play, os: linux, features: &[LaunchFeature::HasQuickPlaysSupport], expected: true },
+        Case { name: "prism arm lwjgl on arm osx", entry: prism_lwjgl_arm.clone(), os: ("osx-arm64", "arm64", "14.2.1"), features: &[], expected: true },
+        Case { name: "prism arm lwjgl on intel osx", entry: prism_lwjgl_arm, os: ("osx", "x86_64", "13.6"), features: &[], expected: false },
+    ]
+}
+
+#[test]
+fn rules_table() {
+    for case in cases() {
+        let (os_name, os_arch, os_version) = case.os;
+        let context = RuleContext::new(os_name, os_arch, os_version).with_features(case.features);
+
+        assert_eq!(
+            context.allows(case.entry["rules"].as_array()),
+            case.expected,
+            "case: {}",
+            case.name
+        );
+    }
+}
+
+#[test]
+fn invalid_version_regex_does_not_match() {
+    let rules = json!([
+        { "action": "allow" },
+        { "action": "disallow", "os": { "name": "osx", "version": "^10\\.(" } }
+    ]);
+
+    assert!(RuleContext::new("osx", "arm64", "10.5.8").allows(rules.as_array()));
+}
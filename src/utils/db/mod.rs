@@ -1,6 +1,6 @@
 use crate::{
     data::{
-        db::{Database, Result},
+        db::{DBError, Database, Result},
         GlobalDataState,
     },
     instance::{
@@ -9,6 +9,7 @@ use crate::{
             overview::{ExportTypes, Overview},
             settings::Settings,
         },
+        loader::Loader,
         Instance,
     },
 };
@@ -21,16 +22,19 @@ pub async fn register_instance<'a>(
 ) -> Result<i64> {
     let instance_name = &instance.name;
     let version = instance.version_id();
+    let loader = instance.loader().as_str();
+    let loader_version = instance.loader_version();
     let dir = instance.paths().instance();
 
     let rec = sqlx::query!(
         r#"
-        INSERT INTO instances (version, loader)
-        VALUES (?1, ?2)
+        INSERT INTO instances (version, loader, loader_version)
+        VALUES (?1, ?2, ?3)
         RETURNING id
         "#,
         version,
-        "vanilla"
+        loader,
+        loader_version
     )
     .fetch_one(&db.pool)
     .await?;
@@ -50,9 +54,47 @@ pub async fn register_instance<'a>(
             rec.id,
             Some(instance_name.clone()),
             version.clone(),
-            String::from("vanilla"),
+            loader.to_string(),
         ))
         .await;
 
     Ok(rec.id)
 }
+
+/// Name of the instance shown to the user, `None` if the overview is missing
+pub async fn instance_name(db: &Database, id: i64) -> Result<Option<String>> {
+    let rec = sqlx::query!(
+        r#"
+        SELECT o.name AS "name?"
+        FROM instances i
+        LEFT JOIN instances_overview o ON o.instance_id = i.id
+        WHERE i.id = ?1
+        "#,
+        id
+    )
+    .fetch_optional(&db.pool)
+    .await?
+    .ok_or_else(|| DBError::NotFound(format!("Instance {id} not found")))?;
+
+    Ok(rec.name)
+}
+
+/// Loader the instance was installed with and its version
+pub async fn instance_loader(db: &Database, id: i64) -> Result<(Loader, Option<String>)> {
+    let rec = sqlx::query!(
+        r#"
+        SELECT loader, loader_version
+        FROM instances
+        WHERE id = ?1
+        "#,
+        id
+    )
+    .fetch_optional(&db.pool)
+    .await?
+    .ok_or_else(|| DBError::NotFound(format!("Instance {id} not found")))?;
+
+    match Loader::from_name(&rec.loader) {
+        Some(loader) => Ok((loader, rec.loader_version)),
+        None => Err(DBError::ResultCorrupted),
+    }
+}
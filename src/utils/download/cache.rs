@@ -0,0 +1,212 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_std::fs;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use surf::Error;
+
+use super::{fetch_json, mirror::Mirrors, JsonResponse};
+
+static GLOBAL_CACHE: OnceLock<Arc<MetadataCache>> = OnceLock::new();
+
+/// Time the response of URLs starting with `prefix` is used without revalidation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TtlRule {
+    pub prefix: String,
+    pub ttl_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CacheSettings {
+    /// Used for URLs which don't match any rule, 0 revalidates on every request
+    pub default_ttl_secs: u64,
+    pub rules: Vec<TtlRule>,
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        CacheSettings {
+            default_ttl_secs: 10 * 60,
+            // Files of packages are addressed by hash and never change
+            rules: vec![TtlRule {
+                prefix: "https://piston-meta.mojang.com/v1/packages/".to_string(),
+                ttl_secs: 7 * 24 * 60 * 60,
+            }],
+        }
+    }
+}
+
+/// Values of `ETag` and `Last-Modified` used for conditional requests
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    url: String,
+    validators: Validators,
+    /// Unix time of the last successful request
+    fetched_at: u64,
+    body: serde_json::Value,
+}
+
+/// Cache of JSON metadata keyed by URL. Responses are stored on disk
+/// and revalidated with conditional requests after their TTL is expired
+#[derive(Debug, Default)]
+pub struct MetadataCache {
+    /// Cache is disabled until the directory is set
+    dir: Mutex<Option<PathBuf>>,
+    settings: Mutex<CacheSettings>,
+}
+
+impl MetadataCache {
+    pub fn new(dir: Option<PathBuf>, settings: CacheSettings) -> Self {
+        MetadataCache {
+            dir: Mutex::new(dir),
+            settings: Mutex::new(settings),
+        }
+    }
+
+    /// Cache used by `download_in_json`
+    pub fn global() -> Arc<MetadataCache> {
+        let cache = GLOBAL_CACHE
+            .get_or_init(|| Arc::new(MetadataCache::new(None, CacheSettings::default())));
+        Arc::clone(cache)
+    }
+
+    pub fn set_dir(&self, dir: PathBuf) {
+        *self.dir.lock().unwrap() = Some(dir);
+    }
+
+    pub fn settings(&self) -> CacheSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn set_settings(&self, settings: CacheSettings) {
+        *self.settings.lock().unwrap() = settings;
+    }
+
+    /// Remove all cached responses
+    pub async fn clear(&self) -> std::io::Result<()> {
+        let dir = match self.dir.lock().unwrap().clone() {
+            Some(dir) => dir,
+            None => return Ok(()),
+        };
+
+        match fs::remove_dir_all(&dir).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the cached JSON while it is fresh, otherwise revalidates it.
+    /// The stale response is returned when the server can't be reached
+    pub async fn get_json(&self, url: &str) -> Result<serde_json::Value, Error> {
+        let path = match self.entry_path(url) {
+            Some(path) => path,
+            None => return fetch_with_mirrors(url, None).await.and_then(into_body),
+        };
+
+        let cached = read_entry(&path).await;
+        if let Some(entry) = &cached {
+            if now_secs().saturating_sub(entry.fetched_at) < self.ttl(url).as_secs() {
+                return Ok(entry.body.clone());
+            }
+        }
+
+        let validators = cached.as_ref().map(|entry| &entry.validators);
+        let mut entry = match (fetch_with_mirrors(url, validators).await, cached) {
+            (Ok(JsonResponse::Modified(body, validators)), _) => CacheEntry {
+                url: url.to_string(),
+                validators,
+                fetched_at: now_secs(),
+                body,
+            },
+            (Ok(JsonResponse::NotModified), Some(entry)) => entry,
+            (Ok(JsonResponse::NotModified), None) => {
+                return Err(Error::from_str(
+                    500,
+                    format!("{} is not modified, but it isn't cached", url),
+                ))
+            }
+            (Err(e), Some(entry)) => {
+                eprintln!(
+                    "Failed to revalidate {}, using the cached response: {}",
+                    url, e
+                );
+                return Ok(entry.body);
+            }
+            (Err(e), None) => return Err(e),
+        };
+        entry.fetched_at = now_secs();
+
+        if let Err(e) = write_entry(&path, &entry).await {
+            eprintln!("Failed to cache {}: {}", url, e);
+        }
+
+        Ok(entry.body)
+    }
+
+    fn ttl(&self, url: &str) -> Duration {
+        let settings = self.settings.lock().unwrap();
+        let ttl_secs = settings
+            .rules
+            .iter()
+            .find(|rule| url.starts_with(&rule.prefix))
+            .map_or(settings.default_ttl_secs, |rule| rule.ttl_secs);
+
+        Duration::from_secs(ttl_secs)
+    }
+
+    fn entry_path(&self, url: &str) -> Option<PathBuf> {
+        let dir = self.dir.lock().unwrap().clone()?;
+        Some(dir.join(format!("{:x}.json", Sha1::digest(url.as_bytes()))))
+    }
+}
+
+async fn fetch_with_mirrors(
+    url: &str,
+    validators: Option<&Validators>,
+) -> Result<JsonResponse, Error> {
+    Mirrors::global()
+        .run(url, |url| async move { fetch_json(&url, validators).await })
+        .await
+}
+
+fn into_body(response: JsonResponse) -> Result<serde_json::Value, Error> {
+    match response {
+        JsonResponse::Modified(body, _) => Ok(body),
+        JsonResponse::NotModified => Err(Error::from_str(500, "Unexpected 304 response")),
+    }
+}
+
+async fn read_entry(path: &PathBuf) -> Option<CacheEntry> {
+    let data = fs::read(path).await.ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+async fn write_entry(path: &PathBuf, entry: &CacheEntry) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).await?;
+    }
+
+    let data = serde_json::to_vec(entry)?;
+
+    // Concurrent readers never see a partially written entry
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, data).await?;
+    fs::rename(&tmp_path, path).await
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
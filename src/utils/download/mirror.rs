@@ -0,0 +1,145 @@
+use std::{
+    env, fs,
+    future::Future,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::definitions::EnvVars;
+
+use super::retry::Retryable;
+
+static GLOBAL_MIRRORS: OnceLock<Arc<Mirrors>> = OnceLock::new();
+
+/// Rewrites URLs starting with `from` to the mirrors
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MirrorRule {
+    /// Prefix of the original URL, e.g. `https://piston-meta.mojang.com`
+    pub from: String,
+    /// Prefixes replacing `from`, which are tried in order
+    pub to: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MirrorConfig {
+    pub rules: Vec<MirrorRule>,
+    /// Request the original URL when all mirrors failed
+    #[serde(default = "default_fallback_to_origin")]
+    pub fallback_to_origin: bool,
+}
+
+fn default_fallback_to_origin() -> bool {
+    true
+}
+
+impl Default for MirrorConfig {
+    fn default() -> Self {
+        MirrorConfig {
+            rules: Vec::new(),
+            fallback_to_origin: true,
+        }
+    }
+}
+
+/// Mirror table applied to every outbound request of the launcher
+#[derive(Debug, Default)]
+pub struct Mirrors {
+    config: Mutex<MirrorConfig>,
+}
+
+impl Mirrors {
+    pub fn new(config: MirrorConfig) -> Self {
+        Mirrors {
+            config: Mutex::new(config),
+        }
+    }
+
+    /// Mirrors shared by all requests. The initial table is read
+    /// from the JSON file set by the `SONATA_MIRRORS` variable
+    pub fn global() -> Arc<Mirrors> {
+        let mirrors = GLOBAL_MIRRORS.get_or_init(|| Arc::new(Mirrors::new(config_from_env())));
+        Arc::clone(mirrors)
+    }
+
+    pub fn config(&self) -> MirrorConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    pub fn set_config(&self, config: MirrorConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// URLs to request instead of `url`, in the order they should be tried.
+    /// The first rule matching the URL is applied
+    pub fn candidates(&self, url: &str) -> Vec<String> {
+        let config = self.config.lock().unwrap();
+
+        let rule = config
+            .rules
+            .iter()
+            .find(|rule| !rule.from.is_empty() && url.starts_with(&rule.from));
+        let mut candidates: Vec<String> = match rule {
+            Some(rule) => rule
+                .to
+                .iter()
+                .map(|prefix| format!("{}{}", prefix, &url[rule.from.len()..]))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        if candidates.is_empty() || config.fallback_to_origin {
+            candidates.push(url.to_string());
+        }
+        candidates.dedup();
+
+        candidates
+    }
+
+    /// Run `operation` with the candidates of `url` until one of them succeeds.
+    /// Any error moves to the next candidate, as a mirror may lack the file,
+    /// except the cancellation, which is returned at once.
+    /// Otherwise the error of the last candidate is returned
+    pub async fn run<T, E, F, Fut>(&self, url: &str, mut operation: F) -> Result<T, E>
+    where
+        E: Retryable + std::fmt::Display,
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut candidates = self.candidates(url).into_iter().peekable();
+
+        loop {
+            // There is always at least one candidate
+            let candidate = candidates.next().unwrap();
+            match operation(candidate.clone()).await {
+                Ok(value) => return Ok(value),
+                Err(e) if !e.is_cancelled() && candidates.peek().is_some() => {
+                    eprintln!(
+                        "Failed to fetch {}, trying the next mirror: {}",
+                        candidate, e
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+fn config_from_env() -> MirrorConfig {
+    let path = match env::var(EnvVars::as_str(&EnvVars::MirrorsConfig)) {
+        Ok(path) => path,
+        Err(_) => return MirrorConfig::default(),
+    };
+
+    let parsed = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|data| serde_json::from_str(&data).map_err(|e| e.to_string()));
+
+    match parsed {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Failed to read mirrors from {}: {}", path, e);
+            MirrorConfig::default()
+        }
+    }
+}
@@ -1,44 +1,194 @@
 use std::{
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
 use async_std::{
-    fs::{create_dir_all, File},
+    fs::{create_dir_all, metadata, remove_file, rename, File, OpenOptions},
 };
 use futures::{AsyncReadExt, AsyncWriteExt};
 use sha1::{Digest, Sha1};
-use surf::{self, Error, Url};
+use surf::{self, Error, StatusCode, Url};
+use thiserror::Error;
 
-use crate::utils::download::buffer::BufferPool;
+use crate::utils::{
+    cancel::CancellationToken,
+    download::{
+        buffer::BufferPool,
+        cache::{MetadataCache, Validators},
+        mirror::Mirrors,
+        progress::{ProgressReporter, ProgressSender},
+        retry::RetryPolicy,
+        scheduler::DownloadScheduler,
+    },
+};
 
 pub mod buffer;
+pub mod cache;
+pub mod mirror;
+pub mod progress;
+pub mod retry;
+pub mod scheduler;
 #[cfg(test)]
 mod tests;
 
 pub const MAX_REDIRECT_COUNT: usize = 100;
 
-pub async fn download(url: String) -> Result<Vec<u8>, String> {
-    match surf::get(url).recv_bytes().await {
-        Ok(response) => return Ok(response),
-        Err(e) => return Err(e.to_string()),
-    }
+#[derive(Debug, Error)]
+pub enum DownloadError {
+    #[error("Request error for {0}: {1}")]
+    Request(String, String),
+
+    #[error("HTTP error {0} when fetching {1}")]
+    Http(StatusCode, String),
+
+    #[error("Too many redirects when fetcing {0}")]
+    TooManyRedirects(String),
+
+    #[error("Redirect (status {0}) without Location for {1}.")]
+    NoLocation(StatusCode, String),
+
+    #[error("Invalid redirect URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("Read 0 bytes from {0}")]
+    Empty(String),
+
+    #[error("SHA1 mismatch at {0}")]
+    ChecksumMismatch(String),
+
+    #[error("{0}")]
+    Io(String),
+
+    #[error("Download of {0} was cancelled")]
+    Cancelled(String),
 }
 
-pub async fn download_in_json<'a>(url: &'a str) -> Result<serde_json::Value, Error> {
-    match surf::get(url).await {
-        Ok(mut response) => match response.body_json::<serde_json::Value>().await {
-            Ok(data) => Ok(data),
-            Err(e) => Err(e),
-        },
-        Err(e) => return Err(e),
-    }
+/// Downloads the whole file into memory, reporting received bytes under `name`.
+/// The download is stopped as soon as `cancel` is cancelled
+pub async fn download_with_progress(
+    url: &str,
+    name: &str,
+    progress: Option<&ProgressSender>,
+    cancel: Option<&CancellationToken>,
+) -> Result<Vec<u8>, String> {
+    let result = Mirrors::global()
+        .run(url, |url| async move { fetch_bytes(&url, name, progress, cancel).await })
+        .await;
+
+    result.map_err(|e| e.to_string())
+}
+
+/// Fetches JSON metadata through the metadata cache
+pub async fn download_in_json(url: &str) -> Result<serde_json::Value, Error> {
+    MetadataCache::global().get_json(url).await
+}
+
+async fn fetch_bytes(
+    url: &str,
+    name: &str,
+    progress: Option<&ProgressSender>,
+    cancel: Option<&CancellationToken>,
+) -> Result<Vec<u8>, DownloadError> {
+    let scheduler = DownloadScheduler::global();
+    let is_cancelled = || cancel.is_some_and(|token| token.is_cancelled());
+
+    RetryPolicy::global()
+        .run(url, || async {
+            let _permit = scheduler.acquire(url).await;
+            let mut response = match surf::get(url).await {
+                Ok(response) => response,
+                Err(e) => return Err(DownloadError::Request(url.to_string(), e.to_string())),
+            };
+            if !response.status().is_success() {
+                return Err(DownloadError::Http(response.status(), url.to_string()));
+            }
+
+            let mut reporter = ProgressReporter::new(progress, name, response.len());
+            let mut data = Vec::with_capacity(response.len().unwrap_or_default());
+            let mut buf = vec![0u8; 64 * 1024];
+            loop {
+                if is_cancelled() {
+                    return Err(DownloadError::Cancelled(name.to_string()));
+                }
+
+                let n = match response.read(&mut buf).await {
+                    Ok(n) => n,
+                    Err(e) => return Err(DownloadError::Request(url.to_string(), e.to_string())),
+                };
+                if n == 0 {
+                    break;
+                }
+
+                scheduler.throttle(n).await;
+                data.extend_from_slice(&buf[..n]);
+                reporter.report(data.len());
+            }
+
+            Ok(data)
+        })
+        .await
+}
+
+/// Response of the conditional request
+pub enum JsonResponse {
+    Modified(serde_json::Value, Validators),
+    NotModified,
+}
+
+async fn fetch_json(url: &str, validators: Option<&Validators>) -> Result<JsonResponse, Error> {
+    let scheduler = DownloadScheduler::global();
+    RetryPolicy::global()
+        .run(url, || async {
+            let _permit = scheduler.acquire(url).await;
+
+            let mut req = surf::get(url);
+            if let Some(validators) = validators {
+                if let Some(etag) = &validators.etag {
+                    req = req.header("If-None-Match", etag.as_str());
+                }
+                if let Some(last_modified) = &validators.last_modified {
+                    req = req.header("If-Modified-Since", last_modified.as_str());
+                }
+            }
+
+            let mut response = req.await?;
+            if response.status() == StatusCode::NotModified {
+                return Ok(JsonResponse::NotModified);
+            }
+            if !response.status().is_success() {
+                return Err(Error::from_str(
+                    response.status(),
+                    format!("HTTP error {} when fetching {}", response.status(), url),
+                ));
+            }
+
+            let validators = Validators {
+                etag: response.header("ETag").map(|v| v.last().to_string()),
+                last_modified: response.header("Last-Modified").map(|v| v.last().to_string()),
+            };
+            let body = response.body_json::<serde_json::Value>().await?;
+
+            Ok(JsonResponse::Modified(body, validators))
+        })
+        .await
+}
+
+/// Calculate sha1 of the file on disk
+pub async fn file_sha1(path: &Path) -> std::io::Result<String> {
+    let mut hasher = Sha1::new();
+    hash_file(path, &mut hasher).await?;
+
+    Ok(format!("{:x}", hasher.finalize()))
 }
 
 pub struct Download<T: Downloadable> {
     save_path: PathBuf,
     object: T,
     buffers_pool: Arc<BufferPool>,
+    cancel: Option<CancellationToken>,
+    retry: RetryPolicy,
+    progress: Option<ProgressSender>,
 }
 
 pub trait Downloadable {
@@ -57,9 +207,41 @@ impl<T: Downloadable + Send + Sync + 'static> Download<T> {
             save_path,
             object,
             buffers_pool,
+            cancel: None,
+            retry: RetryPolicy::global(),
+            progress: None,
         }
     }
 
+    /// Stops the download when the token is cancelled.
+    /// Partially written file is removed in this case
+    pub fn with_cancel(mut self, token: CancellationToken) -> Download<T> {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Reports received bytes while the file is downloaded
+    pub fn with_progress(mut self, sender: ProgressSender) -> Download<T> {
+        self.progress = Some(sender);
+        self
+    }
+
+    /// Repeats failed attempts according to `policy` instead of the global one
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Download<T> {
+        self.retry = policy;
+        self
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|token| token.is_cancelled())
+    }
+
+    /// Downloads the object to a `.part` file next to the save path.
+    /// If the `.part` file is left by an interrupted download, only the rest of it
+    /// is requested, as long as the server supports `Range` requests.
+    /// The file is moved to the save path only after the checksum is matched.
+    /// Failed attempts are repeated according to the retry policy,
+    /// then the next mirror of the URL is tried
     pub async fn download_with_checksum(self) -> Result<T, String> {
         let save_dir = match self.save_path.parent() {
             Some(dir) => dir,
@@ -76,15 +258,52 @@ impl<T: Downloadable + Send + Sync + 'static> Download<T> {
             return Err(e.to_string());
         }
 
-        let mut current_url = self.object.get_url().to_owned();
+        let full_save_path = save_dir.join(file_name);
+        let full_save_path = &full_save_path;
+        let download = &self;
+        let result = Mirrors::global()
+            .run(self.object.get_url(), |url| async move {
+                download
+                    .retry
+                    .run(download.object.get_name(), || download.attempt(full_save_path, &url))
+                    .await
+            })
+            .await;
+
+        match result {
+            Ok(_) => Ok(self.object),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    async fn attempt(&self, full_save_path: &Path, url: &str) -> Result<(), DownloadError> {
+        let part_path = part_path(full_save_path);
+        let scheduler = DownloadScheduler::global();
+
+        let mut current_url = url.to_owned();
         let mut redirect_count: usize = 0;
+        let mut resume_from = match metadata(&part_path).await {
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        };
 
         loop {
-            // Ask for identity encoding
-            let req = surf::get(&current_url.to_string()).header("Accept-Encoding", "identity");
+            if self.is_cancelled() {
+                return Err(DownloadError::Cancelled(self.object.get_name().to_owned()));
+            }
+
+            // Slot is taken for the host which is actually contacted,
+            // so it is released before following a redirect
+            let _permit = scheduler.acquire(&current_url).await;
+
+            // Ask for identity encoding, so the range matches bytes on disk
+            let mut req = surf::get(&current_url).header("Accept-Encoding", "identity");
+            if resume_from > 0 {
+                req = req.header("Range", format!("bytes={}-", resume_from));
+            }
             let mut resp = match req.await {
                 Ok(data) => data,
-                Err(e) => return Err(format!("Request error for {}: {}", current_url, e)),
+                Err(e) => return Err(DownloadError::Request(current_url, e.to_string())),
             };
 
             // Handle redirect from server
@@ -93,27 +312,28 @@ impl<T: Downloadable + Send + Sync + 'static> Download<T> {
                 let location = resp.header("Location").map(|v| v.last());
 
                 if redirect_count >= MAX_REDIRECT_COUNT {
-                    return Err(format!("Too many redirects when fetcing {}", current_url));
+                    return Err(DownloadError::TooManyRedirects(current_url));
                 }
 
                 let location = match location {
                     Some(loc) => loc.as_str(),
                     None => {
-                        return Err(format!(
-                            "Redirect (status {}) without Location for {}.",
-                            status, current_url
-                        ));
+                        return Err(DownloadError::NoLocation(status, current_url));
                     }
                 };
 
                 // Resolve relative Location
-                let base = Url::parse(&current_url)
-                    .map_err(|e| format!("Base URL parse error {}: {}", current_url, e))?;
-                let next_url = match Url::parse(&location) {
+                let base = Url::parse(&current_url).map_err(|e| {
+                    DownloadError::InvalidUrl(format!("Base URL parse error {}: {}", current_url, e))
+                })?;
+                let next_url = match Url::parse(location) {
                     Ok(u) => u, // Absolute
-                    Err(_) => base
-                        .join(&location)
-                        .map_err(|e| format!("Failed to join {} + {}: {}", base, location, e))?,
+                    Err(_) => base.join(location).map_err(|e| {
+                        DownloadError::InvalidUrl(format!(
+                            "Failed to join {} + {}: {}",
+                            base, location, e
+                        ))
+                    })?,
                 };
 
                 current_url = next_url.to_string();
@@ -122,6 +342,18 @@ impl<T: Downloadable + Send + Sync + 'static> Download<T> {
                 continue;
             }
 
+            // `.part` file is already complete or bigger than the remote file.
+            // Without the range requested the response is just an error
+            if resp.status() == StatusCode::RequestedRangeNotSatisfiable && resume_from > 0 {
+                if self.part_matches(&part_path).await {
+                    return finish(&part_path, full_save_path).await;
+                }
+
+                remove_part(&part_path).await;
+                resume_from = 0;
+                continue;
+            }
+
             // Not a redirect -> proceed to download
             println!(
                 "Downloading \"{}\" from URL {}",
@@ -130,51 +362,162 @@ impl<T: Downloadable + Send + Sync + 'static> Download<T> {
             );
 
             if !resp.status().is_success() {
-                return Err(format!(
-                    "HTTP error {} when fetching {}",
-                    resp.status(),
-                    current_url
-                ));
+                return Err(DownloadError::Http(resp.status(), current_url));
+            }
+
+            // Server may ignore the range and send the whole file
+            let resumed = resume_from > 0 && resp.status() == StatusCode::PartialContent;
+            if resumed {
+                let range_start = format!("bytes {}-", resume_from);
+                let content_range = resp.header("Content-Range").map(|v| v.last().as_str());
+                if !content_range.is_some_and(|range| range.starts_with(&range_start)) {
+                    remove_part(&part_path).await;
+                    resume_from = 0;
+                    continue;
+                }
             }
 
             // Prepare file
-            let full_save_path = save_dir.join(file_name);
-            let mut file = match File::create(&full_save_path).await {
+            let mut hasher = Sha1::new();
+            let opened = if resumed {
+                // Bytes downloaded before are hashed first
+                match hash_file(&part_path, &mut hasher).await {
+                    Ok(_) => OpenOptions::new().append(true).open(&part_path).await,
+                    Err(e) => Err(e),
+                }
+            } else {
+                File::create(&part_path).await
+            };
+            let mut file = match opened {
                 Ok(f) => {
-                    println!("Saving to: {}", full_save_path.display());
+                    if resumed {
+                        println!("Resuming {} from {} bytes", part_path.display(), resume_from);
+                    } else {
+                        println!("Saving to: {}", part_path.display());
+                    }
                     f
                 }
-                Err(e) => return Err(format!("Failed to create file {}: {}", full_save_path.display(), e)),
+                Err(e) => {
+                    return Err(DownloadError::Io(format!(
+                        "Failed to create file {}: {}",
+                        part_path.display(),
+                        e
+                    )))
+                }
             };
 
             // Stream -> hasher + file at once with bytes read logging
-            let mut hasher = Sha1::new();
             let mut guard = self.buffers_pool.acquire().await;
             let buf = guard.as_mut_slice();
-            let mut total_read: usize = 0;
+            let mut total_read: usize = if resumed { resume_from as usize } else { 0 };
+            let size = resp.len().map(|len| len + total_read);
+            let mut reporter =
+                ProgressReporter::new(self.progress.as_ref(), self.object.get_name(), size);
             loop {
-                let n = resp.read(buf).await.map_err(|e| e.to_string())?;
+                if self.is_cancelled() {
+                    drop(file);
+                    remove_part(&part_path).await;
+
+                    return Err(DownloadError::Cancelled(self.object.get_name().to_owned()));
+                }
+
+                // `.part` file is kept on read errors, so the next attempt resumes it
+                let n = match resp.read(buf).await {
+                    Ok(n) => n,
+                    Err(e) => return Err(DownloadError::Request(current_url, e.to_string())),
+                };
                 if n == 0 {
                     break;
                 }
 
+                scheduler.throttle(n).await;
                 hasher.update(&buf[..n]);
-                file.write_all(&buf[..n]).await.map_err(|e| e.to_string())?;
+                if let Err(e) = file.write_all(&buf[..n]).await {
+                    return Err(DownloadError::Io(format!(
+                        "Failed to write {}: {}",
+                        part_path.display(),
+                        e
+                    )));
+                }
                 total_read += n;
+                reporter.report(total_read);
+            }
+
+            if let Err(e) = file.flush().await {
+                return Err(DownloadError::Io(format!(
+                    "Failed to write {}: {}",
+                    part_path.display(),
+                    e
+                )));
             }
+            drop(file);
 
             if total_read == 0 {
-                return Err(format!("Read 0 bytes from {}", current_url));
+                remove_part(&part_path).await;
+                return Err(DownloadError::Empty(current_url));
             }
 
             let calculated_sha1 = format!("{:x}", hasher.finalize());
             let expected = self.object.get_hash().to_lowercase();
 
             if calculated_sha1 != expected {
-                return Err(format!("SHA1 mismatch at {}", current_url));
+                // Corrupted data can't be resumed
+                remove_part(&part_path).await;
+                return Err(DownloadError::ChecksumMismatch(current_url));
             } else {
-                return Ok(self.object);
+                return finish(&part_path, full_save_path).await;
             }
         }
     }
+
+    async fn part_matches(&self, part_path: &Path) -> bool {
+        match file_sha1(part_path).await {
+            Ok(sha1) => sha1 == self.object.get_hash().to_lowercase(),
+            Err(_) => false,
+        }
+    }
+
+}
+
+/// Moves the verified `.part` file to the save path
+async fn finish(part_path: &Path, save_path: &Path) -> Result<(), DownloadError> {
+    match rename(part_path, save_path).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(DownloadError::Io(format!(
+            "Failed to move {} to {}: {}",
+            part_path.display(),
+            save_path.display(),
+            e
+        ))),
+    }
+}
+
+/// `client.jar` is downloaded to `client.jar.part`
+fn part_path(save_path: &Path) -> PathBuf {
+    let mut file_name = save_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".part");
+    save_path.with_file_name(file_name)
+}
+
+async fn remove_part(part_path: &Path) {
+    match remove_file(part_path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            eprintln!("Failed to remove {}: {}", part_path.display(), e)
+        }
+        _ => (),
+    }
+}
+
+async fn hash_file(path: &Path, hasher: &mut Sha1) -> std::io::Result<()> {
+    let mut file = File::open(path).await?;
+    let mut buf = vec![0u8; 64 * 1024];
+
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(());
+        }
+
+        hasher.update(&buf[..n]);
+    }
 }
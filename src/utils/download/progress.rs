@@ -0,0 +1,57 @@
+use std::time::{Duration, Instant};
+
+use async_channel::Sender;
+
+/// Minimal time between two progress events of the same file
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Bytes of the file received so far
+#[derive(Debug, Clone, PartialEq)]
+pub struct DownloadProgress {
+    pub name: String,
+    pub current: usize,
+    /// `None` when the server doesn't send `Content-Length`
+    pub size: Option<usize>,
+}
+
+pub type ProgressSender = Sender<DownloadProgress>;
+
+/// Sends progress of a single file not more often than `PROGRESS_INTERVAL`.
+/// The last event with the whole size received is always sent,
+/// so the file is never left partially downloaded in the UI
+pub struct ProgressReporter<'a> {
+    sender: Option<&'a ProgressSender>,
+    name: &'a str,
+    size: Option<usize>,
+    last_report: Instant,
+}
+
+impl<'a> ProgressReporter<'a> {
+    pub fn new(sender: Option<&'a ProgressSender>, name: &'a str, size: Option<usize>) -> Self {
+        ProgressReporter {
+            sender,
+            name,
+            size,
+            last_report: Instant::now(),
+        }
+    }
+
+    pub fn report(&mut self, current: usize) {
+        let Some(sender) = self.sender else {
+            return;
+        };
+
+        let finished = self.size.is_some_and(|size| current >= size);
+        if !finished && self.last_report.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+        self.last_report = Instant::now();
+
+        // Progress is not worth blocking the download
+        let _ = sender.try_send(DownloadProgress {
+            name: self.name.to_string(),
+            current,
+            size: self.size,
+        });
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+
+use async_std::task;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationMilliSeconds};
+use surf::StatusCode;
+
+use super::DownloadError;
+
+static GLOBAL_RETRY_POLICY: OnceLock<Mutex<RetryPolicy>> = OnceLock::new();
+
+/// How failed downloads are repeated.
+/// Delay between attempts grows exponentially up to `max_delay`
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RetryPolicy {
+    /// Total count of attempts, including the first one
+    pub attempts: u32,
+    #[serde(rename = "base_delay_ms")]
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    pub base_delay: Duration,
+    #[serde(rename = "max_delay_ms")]
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    pub max_delay: Duration,
+    /// Part of the delay which is randomized, from 0.0 to 1.0,
+    /// so parallel downloads don't hit the server at the same time
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            jitter: 0.25,
+        }
+    }
+}
+
+/// Errors which may disappear on the next attempt
+pub trait Retryable {
+    fn is_retryable(&self) -> bool;
+
+    /// Nothing should be attempted after the operation is cancelled
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+impl Retryable for DownloadError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            DownloadError::Request(..)
+            | DownloadError::Empty(_)
+            | DownloadError::ChecksumMismatch(_) => true,
+            DownloadError::Http(status, _) => is_retryable_status(*status),
+            _ => false,
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        matches!(self, DownloadError::Cancelled(_))
+    }
+}
+
+impl Retryable for surf::Error {
+    /// Connection errors are reported by surf as 500
+    fn is_retryable(&self) -> bool {
+        is_retryable_status(self.status())
+    }
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::RequestTimeout
+        || status == StatusCode::TooManyRequests
+}
+
+impl RetryPolicy {
+    /// Policy used by all downloads of the launcher
+    pub fn global() -> RetryPolicy {
+        Self::global_lock().lock().unwrap().clone()
+    }
+
+    /// New policy is applied to the downloads which are started after the change
+    pub fn set_global(policy: RetryPolicy) {
+        *Self::global_lock().lock().unwrap() = policy;
+    }
+
+    fn global_lock() -> &'static Mutex<RetryPolicy> {
+        GLOBAL_RETRY_POLICY.get_or_init(|| Mutex::new(RetryPolicy::default()))
+    }
+
+    /// Delay before the next attempt, `attempt` starts from 1
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return delay;
+        }
+
+        // Randomly keyed hasher is enough to spread the attempts
+        let random = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+        delay.mul_f64(1.0 - jitter + 2.0 * jitter * random)
+    }
+
+    /// Run `operation` until it succeeds, fails with an error
+    /// which can't be retried or the attempts are exhausted
+    pub async fn run<T, E, F, Fut>(&self, name: &str, mut operation: F) -> Result<T, E>
+    where
+        E: Retryable + std::fmt::Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 1;
+
+        loop {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(e) if e.is_retryable() && attempt < self.attempts => {
+                    let delay = self.delay(attempt);
+                    eprintln!(
+                        "Failed to download {} ({}/{}), retrying in {:?}: {}",
+                        name, attempt, self.attempts, delay, e
+                    );
+
+                    task::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
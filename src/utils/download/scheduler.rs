@@ -0,0 +1,169 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use async_channel::{bounded, Sender};
+use async_std::task;
+use serde::{Deserialize, Serialize};
+use surf::Url;
+
+static GLOBAL_SCHEDULER: OnceLock<Arc<DownloadScheduler>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct DownloadLimits {
+    /// Downloads running at once across all installers
+    pub max_concurrent: usize,
+    /// Downloads running at once from the same host
+    pub max_per_host: usize,
+    /// Total download speed, not limited when `None`
+    pub bytes_per_second: Option<u64>,
+}
+
+impl Default for DownloadLimits {
+    fn default() -> Self {
+        DownloadLimits {
+            max_concurrent: 64,
+            max_per_host: 32,
+            bytes_per_second: None,
+        }
+    }
+}
+
+/// Limits connections and bandwidth of all downloads of the launcher,
+/// so several installations running at once share the same limits
+#[derive(Debug, Default)]
+pub struct DownloadScheduler {
+    limits: Mutex<DownloadLimits>,
+    slots: Mutex<Slots>,
+    /// Time when the bandwidth is available again
+    next_transfer: Mutex<Option<Instant>>,
+}
+
+#[derive(Debug, Default)]
+struct Slots {
+    active: usize,
+    per_host: HashMap<String, usize>,
+    /// Downloads waiting for a free slot
+    waiters: Vec<Sender<()>>,
+}
+
+/// Slot of the running download, which is freed on Drop
+pub struct DownloadPermit {
+    scheduler: Arc<DownloadScheduler>,
+    host: String,
+}
+
+impl DownloadScheduler {
+    pub fn new(limits: DownloadLimits) -> Self {
+        DownloadScheduler {
+            limits: Mutex::new(limits),
+            ..Default::default()
+        }
+    }
+
+    /// Scheduler shared by all downloads
+    pub fn global() -> Arc<DownloadScheduler> {
+        let scheduler = GLOBAL_SCHEDULER
+            .get_or_init(|| Arc::new(DownloadScheduler::new(DownloadLimits::default())));
+        Arc::clone(scheduler)
+    }
+
+    pub fn limits(&self) -> DownloadLimits {
+        *self.limits.lock().unwrap()
+    }
+
+    /// New limits are applied to the downloads which are started after the change
+    pub fn set_limits(&self, limits: DownloadLimits) {
+        *self.limits.lock().unwrap() = limits;
+        *self.next_transfer.lock().unwrap() = None;
+
+        // Waiting downloads may fit into the new limits
+        self.wake_waiters();
+    }
+
+    /// Wait until the download from the host of `url` can be started
+    pub async fn acquire(self: &Arc<Self>, url: &str) -> DownloadPermit {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(String::from))
+            .unwrap_or_default();
+
+        loop {
+            let waiter = {
+                let limits = self.limits();
+                let mut slots = self.slots.lock().unwrap();
+                let host_active = slots.per_host.get(&host).copied().unwrap_or(0);
+
+                if slots.active < limits.max_concurrent.max(1)
+                    && host_active < limits.max_per_host.max(1)
+                {
+                    slots.active += 1;
+                    *slots.per_host.entry(host.clone()).or_default() += 1;
+
+                    return DownloadPermit {
+                        scheduler: Arc::clone(self),
+                        host,
+                    };
+                }
+
+                let (tx, rx) = bounded(1);
+                slots.waiters.push(tx);
+                rx
+            };
+
+            // Slots are checked again after any download is finished
+            let _ = waiter.recv().await;
+        }
+    }
+
+    /// Wait until `bytes` can be transferred without exceeding the speed limit
+    pub async fn throttle(&self, bytes: usize) {
+        let bytes_per_second = match self.limits().bytes_per_second {
+            Some(limit) if limit > 0 => limit,
+            _ => return,
+        };
+
+        let delay = {
+            let mut next_transfer = self.next_transfer.lock().unwrap();
+            let now = Instant::now();
+            let start = next_transfer.map_or(now, |next| next.max(now));
+            let end = start + Duration::from_secs_f64(bytes as f64 / bytes_per_second as f64);
+
+            *next_transfer = Some(end);
+            end - now
+        };
+
+        task::sleep(delay).await;
+    }
+
+    fn release(&self, host: &str) {
+        {
+            let mut slots = self.slots.lock().unwrap();
+            slots.active = slots.active.saturating_sub(1);
+
+            if let Some(host_active) = slots.per_host.get_mut(host) {
+                *host_active -= 1;
+                if *host_active == 0 {
+                    slots.per_host.remove(host);
+                }
+            }
+        }
+
+        self.wake_waiters();
+    }
+
+    fn wake_waiters(&self) {
+        let waiters = std::mem::take(&mut self.slots.lock().unwrap().waiters);
+        for waiter in waiters {
+            let _ = waiter.try_send(());
+        }
+    }
+}
+
+impl Drop for DownloadPermit {
+    fn drop(&mut self) {
+        self.scheduler.release(&self.host);
+    }
+}
@@ -1,8 +1,17 @@
-use async_std::fs;
+use async_std::{fs, future::timeout};
 use httpmock::{Method::GET, MockServer};
 use tempfile::tempdir;
 
-use super::*;
+use std::time::Duration;
+
+use super::{
+    cache::{CacheSettings, MetadataCache},
+    mirror::{MirrorConfig, MirrorRule, Mirrors},
+    progress::{DownloadProgress, ProgressReporter},
+    retry::RetryPolicy,
+    scheduler::{DownloadLimits, DownloadScheduler},
+    *,
+};
 
 const DEFAULT_BUFFER_SIZE: usize = 4096;
 
@@ -228,3 +237,679 @@ async fn read_zero_bytes() {
     println!("{:#?}", res);
     assert!(res.err().unwrap().to_lowercase().contains("read 0 bytes"));
 }
+
+fn sha1_hex(body: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(body);
+    format!("{:x}", hasher.finalize())
+}
+
+#[async_std::test]
+async fn resume_from_part_file() {
+    let server = MockServer::start_async().await;
+
+    let body = b"Hello, resumed payload";
+    let (head, tail) = body.split_at(7);
+
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/file.bin")
+                .header("Range", "bytes=7-");
+            then.status(206)
+                .header("Content-Range", format!("bytes 7-{}/{}", body.len() - 1, body.len()))
+                .body(tail);
+        })
+        .await;
+
+    let tmp = tempdir().unwrap();
+    let save_path = tmp.path().join("file.bin");
+    let part_path = tmp.path().join("file.bin.part");
+    fs::write(&part_path, head).await.unwrap();
+
+    let object = TestObj {
+        name: "test".into(),
+        url: server.url("/file.bin"),
+        hash: sha1_hex(body),
+    };
+    let pool = Arc::new(BufferPool::new(1, DEFAULT_BUFFER_SIZE));
+    let dl = Download::new(save_path.clone(), object, pool);
+
+    let res = dl.download_with_checksum().await;
+    assert!(res.is_ok(), "Expected Ok(_), got Err: {:?}", res.err());
+    mock.assert_async().await;
+
+    assert_eq!(fs::read(&save_path).await.unwrap().as_slice(), body);
+    assert!(!part_path.exists());
+}
+
+#[async_std::test]
+async fn range_ignored_by_server() {
+    let server = MockServer::start_async().await;
+
+    let body = b"Whole file sent again";
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/file.bin");
+            then.status(200).body(body);
+        })
+        .await;
+
+    let tmp = tempdir().unwrap();
+    let save_path = tmp.path().join("file.bin");
+    let part_path = tmp.path().join("file.bin.part");
+    fs::write(&part_path, b"stale").await.unwrap();
+
+    let object = TestObj {
+        name: "test".into(),
+        url: server.url("/file.bin"),
+        hash: sha1_hex(body),
+    };
+    let pool = Arc::new(BufferPool::new(1, DEFAULT_BUFFER_SIZE));
+    let dl = Download::new(save_path.clone(), object, pool);
+
+    let res = dl.download_with_checksum().await;
+    assert!(res.is_ok(), "Expected Ok(_), got Err: {:?}", res.err());
+
+    assert_eq!(fs::read(&save_path).await.unwrap().as_slice(), body);
+    assert!(!part_path.exists());
+}
+
+#[async_std::test]
+async fn complete_part_file_not_satisfiable() {
+    let server = MockServer::start_async().await;
+
+    let body = b"Already downloaded";
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/file.bin");
+            then.status(416);
+        })
+        .await;
+
+    let tmp = tempdir().unwrap();
+    let save_path = tmp.path().join("file.bin");
+    let part_path = tmp.path().join("file.bin.part");
+    fs::write(&part_path, body).await.unwrap();
+
+    let object = TestObj {
+        name: "test".into(),
+        url: server.url("/file.bin"),
+        hash: sha1_hex(body),
+    };
+    let pool = Arc::new(BufferPool::new(1, DEFAULT_BUFFER_SIZE));
+    let dl = Download::new(save_path.clone(), object, pool);
+
+    let res = dl.download_with_checksum().await;
+    assert!(res.is_ok(), "Expected Ok(_), got Err: {:?}", res.err());
+
+    assert_eq!(fs::read(&save_path).await.unwrap().as_slice(), body);
+    assert!(!part_path.exists());
+}
+
+#[async_std::test]
+async fn not_satisfiable_without_range_fails() {
+    let server = MockServer::start_async().await;
+
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/file.bin");
+            then.status(416);
+        })
+        .await;
+
+    let tmp = tempdir().unwrap();
+    let object = TestObj {
+        name: "test".into(),
+        url: server.url("/file.bin"),
+        hash: sha1_hex(b""),
+    };
+    let pool = Arc::new(BufferPool::new(1, DEFAULT_BUFFER_SIZE));
+    let dl = Download::new(tmp.path().join("file.bin"), object, pool).with_retry(instant_retry(3));
+
+    let res = timeout(Duration::from_secs(5), dl.download_with_checksum())
+        .await
+        .expect("Download must not loop on 416");
+    assert!(res.err().unwrap().contains("416"));
+    assert_eq!(mock.hits_async().await, 1);
+}
+
+#[async_std::test]
+async fn checksum_mismatch_keeps_save_path_untouched() {
+    let server = MockServer::start_async().await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/file.bin");
+            then.status(200).body(b"New corrupted content");
+        })
+        .await;
+
+    let tmp = tempdir().unwrap();
+    let save_path = tmp.path().join("file.bin");
+    let part_path = tmp.path().join("file.bin.part");
+    fs::write(&save_path, b"Old content").await.unwrap();
+
+    let object = TestObj {
+        name: "test".into(),
+        url: server.url("/file.bin"),
+        hash: "0000000000000000000000000000000000000000".into(),
+    };
+    let pool = Arc::new(BufferPool::new(1, DEFAULT_BUFFER_SIZE));
+    let dl = Download::new(save_path.clone(), object, pool).with_retry(instant_retry(1));
+
+    let res = dl.download_with_checksum().await;
+    assert!(res.err().unwrap().to_lowercase().contains("sha1"));
+
+    // Only a verified file replaces the old one
+    assert_eq!(fs::read(&save_path).await.unwrap().as_slice(), b"Old content");
+    assert!(!part_path.exists());
+}
+
+fn instant_retry(attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        attempts,
+        base_delay: Duration::ZERO,
+        jitter: 0.0,
+        ..Default::default()
+    }
+}
+
+#[async_std::test]
+async fn server_error_retried_until_exhausted() {
+    let server = MockServer::start_async().await;
+
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/file.bin");
+            then.status(503);
+        })
+        .await;
+
+    let tmp = tempdir().unwrap();
+    let object = TestObj {
+        name: "test".into(),
+        url: server.url("/file.bin"),
+        hash: sha1_hex(b""),
+    };
+    let pool = Arc::new(BufferPool::new(1, DEFAULT_BUFFER_SIZE));
+    let dl = Download::new(tmp.path().join("file.bin"), object, pool).with_retry(instant_retry(3));
+
+    let res = dl.download_with_checksum().await;
+    assert!(res.err().unwrap().contains("503"));
+    assert_eq!(mock.hits_async().await, 3);
+}
+
+#[async_std::test]
+async fn client_error_not_retried() {
+    let server = MockServer::start_async().await;
+
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/file.bin");
+            then.status(404);
+        })
+        .await;
+
+    let tmp = tempdir().unwrap();
+    let object = TestObj {
+        name: "test".into(),
+        url: server.url("/file.bin"),
+        hash: sha1_hex(b""),
+    };
+    let pool = Arc::new(BufferPool::new(1, DEFAULT_BUFFER_SIZE));
+    let dl = Download::new(tmp.path().join("file.bin"), object, pool).with_retry(instant_retry(3));
+
+    let res = dl.download_with_checksum().await;
+    assert!(res.err().unwrap().contains("404"));
+    assert_eq!(mock.hits_async().await, 1);
+}
+
+#[async_std::test]
+async fn checksum_mismatch_retried() {
+    let server = MockServer::start_async().await;
+
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/file.bin");
+            then.status(200).body(b"Corrupted on the way");
+        })
+        .await;
+
+    let tmp = tempdir().unwrap();
+    let object = TestObj {
+        name: "test".into(),
+        url: server.url("/file.bin"),
+        hash: "0000000000000000000000000000000000000000".into(),
+    };
+    let pool = Arc::new(BufferPool::new(1, DEFAULT_BUFFER_SIZE));
+    let dl = Download::new(tmp.path().join("file.bin"), object, pool).with_retry(instant_retry(2));
+
+    let res = dl.download_with_checksum().await;
+    assert!(res.err().unwrap().to_lowercase().contains("sha1"));
+    assert_eq!(mock.hits_async().await, 2);
+}
+
+#[test]
+fn retry_delay_grows_exponentially() {
+    let policy = RetryPolicy {
+        attempts: 10,
+        base_delay: Duration::from_millis(100),
+        max_delay: Duration::from_millis(1000),
+        jitter: 0.0,
+    };
+
+    assert_eq!(policy.delay(1), Duration::from_millis(100));
+    assert_eq!(policy.delay(2), Duration::from_millis(200));
+    assert_eq!(policy.delay(4), Duration::from_millis(800));
+    assert_eq!(policy.delay(5), Duration::from_millis(1000));
+    assert_eq!(policy.delay(u32::MAX), Duration::from_millis(1000));
+}
+
+#[test]
+fn retry_delay_jitter_within_bounds() {
+    let policy = RetryPolicy {
+        attempts: 3,
+        base_delay: Duration::from_millis(1000),
+        max_delay: Duration::from_secs(10),
+        jitter: 0.5,
+    };
+
+    for _ in 0..100 {
+        let delay = policy.delay(1);
+        assert!(delay >= Duration::from_millis(500), "{:?}", delay);
+        assert!(delay <= Duration::from_millis(1500), "{:?}", delay);
+    }
+}
+
+#[test]
+fn retry_policy_delays_in_milliseconds() {
+    let policy: RetryPolicy = serde_json::from_value(serde_json::json!({
+        "attempts": 2,
+        "base_delay_ms": 250,
+        "max_delay_ms": 5000,
+        "jitter": 0.0
+    }))
+    .unwrap();
+
+    assert_eq!(policy.base_delay, Duration::from_millis(250));
+    assert_eq!(policy.max_delay, Duration::from_secs(5));
+    assert_eq!(serde_json::to_value(&policy).unwrap()["max_delay_ms"], 5000);
+}
+
+#[async_std::test]
+async fn scheduler_limits_concurrent_downloads() {
+    let scheduler = Arc::new(DownloadScheduler::new(DownloadLimits {
+        max_concurrent: 1,
+        ..Default::default()
+    }));
+
+    let permit = scheduler.acquire("https://a.example/file").await;
+    let blocked = timeout(
+        Duration::from_millis(100),
+        scheduler.acquire("https://b.example/file"),
+    )
+    .await;
+    assert!(blocked.is_err());
+
+    drop(permit);
+    let acquired = timeout(
+        Duration::from_millis(100),
+        scheduler.acquire("https://b.example/file"),
+    )
+    .await;
+    assert!(acquired.is_ok());
+}
+
+#[async_std::test]
+async fn scheduler_limits_downloads_per_host() {
+    let scheduler = Arc::new(DownloadScheduler::new(DownloadLimits {
+        max_concurrent: 10,
+        max_per_host: 1,
+        bytes_per_second: None,
+    }));
+
+    let _permit = scheduler.acquire("https://a.example/first").await;
+    let same_host = timeout(
+        Duration::from_millis(100),
+        scheduler.acquire("https://a.example/second"),
+    )
+    .await;
+    assert!(same_host.is_err());
+
+    let other_host = timeout(
+        Duration::from_millis(100),
+        scheduler.acquire("https://b.example/file"),
+    )
+    .await;
+    assert!(other_host.is_ok());
+}
+
+#[async_std::test]
+async fn scheduler_wakes_waiters_on_new_limits() {
+    let scheduler = Arc::new(DownloadScheduler::new(DownloadLimits {
+        max_concurrent: 1,
+        ..Default::default()
+    }));
+
+    let _permit = scheduler.acquire("https://a.example/file").await;
+    let waiting = {
+        let scheduler = Arc::clone(&scheduler);
+        async_std::task::spawn(async move {
+            let _permit = scheduler.acquire("https://b.example/file").await;
+        })
+    };
+
+    async_std::task::sleep(Duration::from_millis(50)).await;
+    scheduler.set_limits(DownloadLimits {
+        max_concurrent: 2,
+        ..Default::default()
+    });
+
+    assert!(timeout(Duration::from_millis(500), waiting).await.is_ok());
+}
+
+#[async_std::test]
+async fn scheduler_throttles_bandwidth() {
+    let scheduler = DownloadScheduler::new(DownloadLimits {
+        bytes_per_second: Some(1000),
+        ..Default::default()
+    });
+
+    let started = std::time::Instant::now();
+    scheduler.throttle(100).await;
+    scheduler.throttle(100).await;
+
+    assert!(started.elapsed() >= Duration::from_millis(200));
+}
+
+#[async_std::test]
+async fn file_sha1_matches_content() {
+    // Bigger than the read buffer, so the file is hashed in chunks
+    let body = vec![7u8; 100 * 1024];
+    let mut hasher = Sha1::new();
+    hasher.update(&body);
+    let expected_hash = format!("{:x}", hasher.finalize());
+
+    let tmp = tempdir().unwrap();
+    let path = tmp.path().join("file.bin");
+    fs::write(&path, &body).await.unwrap();
+
+    assert_eq!(file_sha1(&path).await.unwrap(), expected_hash);
+}
+
+#[async_std::test]
+async fn file_sha1_missing_file() {
+    let tmp = tempdir().unwrap();
+
+    assert!(file_sha1(&tmp.path().join("missing.bin")).await.is_err());
+}
+
+#[async_std::test]
+async fn cancelled_download_into_memory() {
+    let server = MockServer::start_async().await;
+
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/file.bin");
+            then.status(200).body(b"Never read");
+        })
+        .await;
+
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+
+    let res = download_with_progress(&server.url("/file.bin"), "file.bin", None, Some(&cancel)).await;
+    assert!(res.err().unwrap().contains("cancelled"));
+    assert_eq!(mock.hits_async().await, 1);
+}
+
+#[async_std::test]
+async fn progress_is_throttled() {
+    let (tx, rx) = async_channel::unbounded();
+    let mut reporter = ProgressReporter::new(Some(&tx), "client.jar", Some(1024));
+
+    // Chunks received right after the start are not reported
+    reporter.report(256);
+    assert!(rx.try_recv().is_err());
+
+    async_std::task::sleep(Duration::from_millis(300)).await;
+    reporter.report(512);
+    reporter.report(768);
+
+    assert_eq!(
+        rx.try_recv().unwrap(),
+        DownloadProgress {
+            name: "client.jar".to_string(),
+            current: 512,
+            size: Some(1024),
+        }
+    );
+    assert!(rx.try_recv().is_err());
+}
+
+#[async_std::test]
+async fn progress_reports_finished_file() {
+    let (tx, rx) = async_channel::unbounded();
+    let mut reporter = ProgressReporter::new(Some(&tx), "client.jar", Some(1024));
+
+    reporter.report(512);
+    reporter.report(1024);
+
+    assert_eq!(
+        rx.try_recv().unwrap(),
+        DownloadProgress {
+            name: "client.jar".to_string(),
+            current: 1024,
+            size: Some(1024),
+        }
+    );
+    assert!(rx.try_recv().is_err());
+}
+
+fn mirrors(fallback_to_origin: bool) -> Mirrors {
+    Mirrors::new(MirrorConfig {
+        rules: vec![MirrorRule {
+            from: "https://piston-meta.mojang.com".into(),
+            to: vec![
+                "http://localhost:8000/mojang".into(),
+                "https://bmclapi2.bangbang93.com".into(),
+            ],
+        }],
+        fallback_to_origin,
+    })
+}
+
+#[test]
+fn mirror_rewrites_prefix() {
+    let url = "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
+
+    assert_eq!(
+        mirrors(true).candidates(url),
+        vec![
+            "http://localhost:8000/mojang/mc/game/version_manifest_v2.json",
+            "https://bmclapi2.bangbang93.com/mc/game/version_manifest_v2.json",
+            url,
+        ]
+    );
+    assert_eq!(mirrors(false).candidates(url).len(), 2);
+}
+
+#[test]
+fn mirror_keeps_unmatched_url() {
+    let url = "https://meta.prismlauncher.org/v1/net.minecraft/index.json";
+    assert_eq!(mirrors(false).candidates(url), vec![url]);
+}
+
+#[async_std::test]
+async fn mirror_falls_back_to_next_candidate() {
+    let mut requested = Vec::new();
+    let result: Result<String, DownloadError> = mirrors(true)
+        .run("https://piston-meta.mojang.com/file.json", |url| {
+            requested.push(url.clone());
+            async move {
+                match url.starts_with("https://bmclapi2") {
+                    true => Ok(url),
+                    false => Err(DownloadError::Request(url, "not available".into())),
+                }
+            }
+        })
+        .await;
+
+    assert_eq!(result.unwrap(), "https://bmclapi2.bangbang93.com/file.json");
+    assert_eq!(requested.len(), 2);
+}
+
+#[async_std::test]
+async fn mirror_returns_last_error() {
+    let result: Result<(), DownloadError> = mirrors(false)
+        .run("https://piston-meta.mojang.com/file.json", |url| async move {
+            Err(DownloadError::Http(StatusCode::ServiceUnavailable, url))
+        })
+        .await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        DownloadError::Http(_, url) if url == "https://bmclapi2.bangbang93.com/file.json"
+    ));
+}
+
+#[async_std::test]
+async fn mirror_without_file_falls_back_to_origin() {
+    let mut requested = Vec::new();
+    let result: Result<String, DownloadError> = mirrors(true)
+        .run("https://piston-meta.mojang.com/file.json", |url| {
+            requested.push(url.clone());
+            async move {
+                match url.starts_with("https://piston-meta") {
+                    true => Ok(url),
+                    false => Err(DownloadError::Http(StatusCode::NotFound, url)),
+                }
+            }
+        })
+        .await;
+
+    assert_eq!(result.unwrap(), "https://piston-meta.mojang.com/file.json");
+    assert_eq!(requested.len(), 3);
+}
+
+#[async_std::test]
+async fn mirror_stops_on_cancelled_download() {
+    let mut requested = 0;
+    let result: Result<(), DownloadError> = mirrors(true)
+        .run("https://piston-meta.mojang.com/file.json", |url| {
+            requested += 1;
+            async move { Err(DownloadError::Cancelled(url)) }
+        })
+        .await;
+
+    assert!(matches!(result.unwrap_err(), DownloadError::Cancelled(_)));
+    assert_eq!(requested, 1);
+}
+
+fn metadata_cache(dir: &Path, default_ttl_secs: u64) -> MetadataCache {
+    MetadataCache::new(
+        Some(dir.to_path_buf()),
+        CacheSettings {
+            default_ttl_secs,
+            rules: Vec::new(),
+        },
+    )
+}
+
+#[async_std::test]
+async fn fresh_metadata_is_not_requested() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/index.json");
+            then.status(200).body(r#"{"versions": []}"#);
+        })
+        .await;
+
+    let tmp = tempdir().unwrap();
+    let cache = metadata_cache(tmp.path(), 600);
+    let url = server.url("/index.json");
+
+    let first = cache.get_json(&url).await.unwrap();
+    let second = cache.get_json(&url).await.unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(mock.hits_async().await, 1);
+}
+
+#[async_std::test]
+async fn expired_metadata_is_revalidated() {
+    let server = MockServer::start_async().await;
+    let not_modified = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/index.json")
+                .header("If-None-Match", "\"v1\"");
+            then.status(304);
+        })
+        .await;
+    let modified = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/index.json");
+            then.status(200)
+                .header("ETag", "\"v1\"")
+                .body(r#"{"versions": ["1.20.1"]}"#);
+        })
+        .await;
+
+    let tmp = tempdir().unwrap();
+    let cache = metadata_cache(tmp.path(), 0);
+    let url = server.url("/index.json");
+
+    cache.get_json(&url).await.unwrap();
+    let revalidated = cache.get_json(&url).await.unwrap();
+
+    assert_eq!(revalidated["versions"][0], "1.20.1");
+    assert_eq!(modified.hits_async().await, 1);
+    assert_eq!(not_modified.hits_async().await, 1);
+}
+
+#[async_std::test]
+async fn stale_metadata_used_when_server_fails() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/index.json");
+            then.status(200).body(r#"{"versions": []}"#);
+        })
+        .await;
+
+    let tmp = tempdir().unwrap();
+    let cache = metadata_cache(tmp.path(), 0);
+    let url = server.url("/index.json");
+    cache.get_json(&url).await.unwrap();
+
+    mock.delete_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/index.json");
+            then.status(404);
+        })
+        .await;
+
+    assert_eq!(cache.get_json(&url).await.unwrap()["versions"], serde_json::json!([]));
+}
+
+#[async_std::test]
+async fn metadata_without_cache_dir() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/index.json");
+            then.status(200).body(r#"{"versions": []}"#);
+        })
+        .await;
+
+    let cache = MetadataCache::new(None, CacheSettings::default());
+    let url = server.url("/index.json");
+    cache.get_json(&url).await.unwrap();
+    cache.get_json(&url).await.unwrap();
+
+    assert_eq!(mock.hits_async().await, 2);
+}
@@ -63,6 +63,42 @@ pub fn coord_to_path(coord: &str) -> String {
     out
 }
 
+/// Converts a coordinate like `group:artifact:version[:classifier][@extension]`
+/// into the path of the artifact file in a Maven repository
+///
+/// Examples:
+/// - "net.fabricmc:fabric-loader:0.15.11" -> "net/fabricmc/fabric-loader/0.15.11/fabric-loader-0.15.11.jar"
+/// - "org.lwjgl:lwjgl:3.3.1:natives-linux" -> "org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux.jar"
+pub fn artifact_path(coord: &str) -> Option<String> {
+    let (coord, extension) = match coord.split_once('@') {
+        Some((coord, extension)) => (coord, extension),
+        None => (coord, "jar"),
+    };
+
+    let parts: Vec<&str> = coord.split(':').collect();
+    let (group, artifact, version, classifier) = match parts[..] {
+        [group, artifact, version] => (group, artifact, version, None),
+        [group, artifact, version, classifier] => (group, artifact, version, Some(classifier)),
+        _ => return None,
+    };
+    if parts.iter().any(|part| part.is_empty()) {
+        return None;
+    }
+
+    let file_name = match classifier {
+        Some(classifier) => format!("{}-{}-{}.{}", artifact, version, classifier, extension),
+        None => format!("{}-{}.{}", artifact, version, extension),
+    };
+
+    Some(format!(
+        "{}/{}/{}/{}",
+        group.replace('.', "/"),
+        artifact,
+        version,
+        file_name
+    ))
+}
+
 pub fn build_file_path<S, P>(libs_dir: &P, maven_path: S) -> String
 where
     S: Into<String>,
@@ -83,7 +119,7 @@ mod tests {
 
     use crate::utils::maven::build_file_path;
 
-    use super::coord_to_path;
+    use super::{artifact_path, coord_to_path};
 
     #[test]
     fn no_first_slash() {
@@ -162,4 +198,30 @@ mod tests {
         let s = "group:artifact";
         assert_eq!(coord_to_path(s), "group/artifact");
     }
+
+    #[test]
+    fn artifact_path_of_jar() {
+        assert_eq!(
+            artifact_path("net.fabricmc:fabric-loader:0.15.11").as_deref(),
+            Some("net/fabricmc/fabric-loader/0.15.11/fabric-loader-0.15.11.jar")
+        );
+    }
+
+    #[test]
+    fn artifact_path_with_classifier_and_extension() {
+        assert_eq!(
+            artifact_path("org.lwjgl:lwjgl:3.3.1:natives-linux").as_deref(),
+            Some("org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux.jar")
+        );
+        assert_eq!(
+            artifact_path("com.example:data:1.0@zip").as_deref(),
+            Some("com/example/data/1.0/data-1.0.zip")
+        );
+    }
+
+    #[test]
+    fn artifact_path_of_incomplete_coord() {
+        assert_eq!(artifact_path("group:artifact"), None);
+        assert_eq!(artifact_path("group::1.0"), None);
+    }
 }
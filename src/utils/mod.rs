@@ -6,8 +6,8 @@ use home::home_dir;
 
 use crate::data::definitions::EnvVars;
 
+pub mod cancel;
 pub mod download;
-pub mod metacache;
 pub mod maven;
 pub mod unify;
 pub mod db;
@@ -0,0 +1,278 @@
+use std::cmp::Ordering;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::{MetaProviders, UnifiedVersion};
+
+const DEFAULT_PER_PAGE: usize = 50;
+const MAX_PER_PAGE: usize = 500;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+#[derive(TS)]
+pub enum ReleaseType {
+    Release,
+    Snapshot,
+    Beta,
+    Alpha,
+    /// Experiments and types unknown to the launcher
+    Other,
+}
+
+impl ReleaseType {
+    /// Accepts names of the manifests (`old_beta`) and the short ones (`beta`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "release" => Some(ReleaseType::Release),
+            "snapshot" => Some(ReleaseType::Snapshot),
+            "beta" | "old_beta" => Some(ReleaseType::Beta),
+            "alpha" | "old_alpha" => Some(ReleaseType::Alpha),
+            "other" => Some(ReleaseType::Other),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(TS)]
+pub struct CatalogVersion {
+    pub id: String,
+    /// Version manifest
+    pub url: String,
+    pub release_type: ReleaseType,
+    pub release_time: Option<DateTime<Utc>>,
+    pub sha1: Option<String>,
+}
+
+impl From<UnifiedVersion<'_>> for CatalogVersion {
+    fn from(version: UnifiedVersion<'_>) -> Self {
+        CatalogVersion {
+            id: version.id.to_string(),
+            url: version.url,
+            release_type: version
+                .release_type
+                .and_then(ReleaseType::from_name)
+                .unwrap_or(ReleaseType::Other),
+            release_time: version
+                .release_time
+                .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+                .map(|time| time.with_timezone(&Utc)),
+            sha1: version.sha1.map(String::from),
+        }
+    }
+}
+
+/// Single page of the versions matching the query
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(TS)]
+pub struct CatalogPage {
+    pub versions: Vec<CatalogVersion>,
+    /// Count of the versions matching the query on all pages
+    pub total: usize,
+    /// Starts from 1
+    pub page: usize,
+    pub per_page: usize,
+    pub pages: usize,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogSort {
+    #[default]
+    ReleaseTime,
+    /// Versions like `1.20.1` are compared by their numbers
+    Id,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Query of the catalog endpoint, all fields are optional
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct CatalogQuery {
+    pub provider: MetaProviders,
+    /// Comma separated release types, e.g. `release,snapshot`
+    pub types: Option<String>,
+    /// RFC 3339 time or date, e.g. `2020-01-01`
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// Substring of the id or a version requirement like `>=1.18`, `<1.13` or `1.20.x`
+    pub search: Option<String>,
+    pub sort: CatalogSort,
+    pub order: SortOrder,
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+}
+
+enum Search {
+    Substring(String),
+    Compare(Vec<Ordering>, Vec<u64>),
+    Prefix(Vec<u64>),
+}
+
+impl CatalogQuery {
+    /// Filter, sort and paginate `versions`
+    pub fn apply(&self, mut versions: Vec<CatalogVersion>) -> Result<CatalogPage, String> {
+        let types = match &self.types {
+            Some(types) => Some(
+                types
+                    .split(',')
+                    .map(|name| {
+                        ReleaseType::from_name(name.trim())
+                            .ok_or_else(|| format!("Unknown release type {}", name))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None => None,
+        };
+        let since = self.since.as_deref().map(|time| parse_time(time, false)).transpose()?;
+        let until = self.until.as_deref().map(|time| parse_time(time, true)).transpose()?;
+        let search = self.search.as_deref().map(parse_search);
+
+        versions.retain(|version| {
+            if types
+                .as_ref()
+                .is_some_and(|types| !types.contains(&version.release_type))
+            {
+                return false;
+            }
+
+            // Versions without the time can't be checked against the range
+            if since.is_some() || until.is_some() {
+                let Some(time) = version.release_time else {
+                    return false;
+                };
+                if since.is_some_and(|since| time < since)
+                    || until.is_some_and(|until| time > until)
+                {
+                    return false;
+                }
+            }
+
+            search
+                .as_ref()
+                .is_none_or(|search| search.matches(&version.id))
+        });
+
+        match self.sort {
+            CatalogSort::ReleaseTime => versions.sort_by_key(|version| version.release_time),
+            CatalogSort::Id => versions.sort_by(|a, b| cmp_ids(&a.id, &b.id)),
+        }
+        if self.order == SortOrder::Desc {
+            versions.reverse();
+        }
+
+        let per_page = self
+            .per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .clamp(1, MAX_PER_PAGE);
+        let page = self.page.unwrap_or(1).max(1);
+        let total = versions.len();
+
+        Ok(CatalogPage {
+            versions: versions
+                .into_iter()
+                .skip((page - 1).saturating_mul(per_page))
+                .take(per_page)
+                .collect(),
+            total,
+            page,
+            per_page,
+            pages: total.div_ceil(per_page),
+        })
+    }
+}
+
+impl Search {
+    fn matches(&self, id: &str) -> bool {
+        match self {
+            Search::Substring(substring) => id.to_lowercase().contains(substring),
+            Search::Compare(allowed, required) => parse_version(id)
+                .is_some_and(|version| allowed.contains(&cmp_versions(&version, required))),
+            Search::Prefix(prefix) => {
+                parse_version(id).is_some_and(|version| version.starts_with(prefix))
+            }
+        }
+    }
+}
+
+fn parse_search(search: &str) -> Search {
+    let search = search.trim();
+
+    let operators = [
+        (">=", vec![Ordering::Greater, Ordering::Equal]),
+        ("<=", vec![Ordering::Less, Ordering::Equal]),
+        (">", vec![Ordering::Greater]),
+        ("<", vec![Ordering::Less]),
+        ("=", vec![Ordering::Equal]),
+    ];
+    for (operator, allowed) in operators {
+        if let Some(version) = search
+            .strip_prefix(operator)
+            .and_then(|v| parse_version(v.trim()))
+        {
+            return Search::Compare(allowed, version);
+        }
+    }
+
+    let wildcard = search
+        .strip_suffix(".x")
+        .or_else(|| search.strip_suffix(".*"));
+    if let Some(prefix) = wildcard.and_then(parse_version) {
+        return Search::Prefix(prefix);
+    }
+
+    Search::Substring(search.to_lowercase())
+}
+
+/// Numbers of the versions like `1.20.1`, snapshots and other ids are `None`
+fn parse_version(id: &str) -> Option<Vec<u64>> {
+    id.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// Missing numbers are zeros, so `1.20` equals `1.20.0`
+fn cmp_versions(a: &[u64], b: &[u64]) -> Ordering {
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|i| a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Numbered versions go before other ids, which are compared as strings
+fn cmp_ids(a: &str, b: &str) -> Ordering {
+    match (parse_version(a), parse_version(b)) {
+        (Some(a), Some(b)) => cmp_versions(&a, &b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.cmp(b),
+    }
+}
+
+/// Date is the start of the day, or its end with `end_of_day`,
+/// so both bounds of the range include the whole day
+fn parse_time(time: &str, end_of_day: bool) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(time) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    match NaiveDate::parse_from_str(time, "%Y-%m-%d") {
+        Ok(date) if end_of_day => Ok(date
+            .and_hms_nano_opt(23, 59, 59, 999_999_999)
+            .unwrap_or_default()
+            .and_utc()),
+        Ok(date) => Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()),
+        Err(_) => Err(format!(
+            "Invalid time {}, expected RFC 3339 or YYYY-MM-DD",
+            time
+        )),
+    }
+}
@@ -1,12 +1,23 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use surf::StatusCode;
 
 use crate::utils::download::download_in_json;
 
-#[derive(Serialize)]
+use catalog::CatalogVersion;
+
+pub mod catalog;
+#[cfg(test)]
+mod tests;
+
+#[derive(Serialize, Debug, PartialEq)]
 pub struct UnifiedVersion<'a> {
     id: &'a str,
     url: String,
+    /// `release`, `snapshot`, `old_beta` or `old_alpha`
+    release_type: Option<&'a str>,
+    release_time: Option<&'a str>,
+    /// Only Mojang manifest provides sha1 of the version manifest
+    sha1: Option<&'a str>,
 }
 
 pub struct UnifiedVersionsData {
@@ -14,20 +25,27 @@ pub struct UnifiedVersionsData {
     manifest_type: MetaProviders,
 }
 
-#[allow(dead_code)]
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
 pub enum MetaProviders {
     Mojang,
+    #[default]
     Prism,
 }
 
-impl<'a> UnifiedVersionsData {
-    pub async fn new(manifest_type: MetaProviders) -> Result<Self, StatusCode> {
-        let manifest_url = match manifest_type {
+impl MetaProviders {
+    /// List of Minecraft versions of the provider
+    pub fn manifest_url(&self) -> &'static str {
+        match self {
             MetaProviders::Mojang => "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json",
             MetaProviders::Prism => "https://meta.prismlauncher.org/v1/net.minecraft/index.json",
-        };
+        }
+    }
+}
 
-        let value = match download_in_json(manifest_url).await {
+impl<'a> UnifiedVersionsData {
+    pub async fn new(manifest_type: MetaProviders) -> Result<Self, StatusCode> {
+        let value = match download_in_json(manifest_type.manifest_url()).await {
             Ok(val) => val,
             Err(e) => return Err(e.status()),
         };
@@ -38,9 +56,19 @@ impl<'a> UnifiedVersionsData {
         })
     }
 
+    /// Versions of the manifest for the catalog
+    pub fn catalog(&self) -> Result<Vec<CatalogVersion>, String> {
+        let versions = match self.manifest_type {
+            MetaProviders::Mojang => Self::extract_mojang(&self.manifest)?,
+            MetaProviders::Prism => Self::extract_prism(&self.manifest)?,
+        };
+
+        Ok(versions.into_iter().map(CatalogVersion::from).collect())
+    }
+
     pub fn build(self) -> Result<String, String> {
         let unified_versions_result = match self.manifest_type {
-            MetaProviders::Mojang => Err("Not implemented".to_string()),
+            MetaProviders::Mojang => Self::extract_mojang(&self.manifest),
             MetaProviders::Prism => Self::extract_prism(&self.manifest),
         };
 
@@ -65,7 +93,13 @@ impl<'a> UnifiedVersionsData {
                     if let Some(id) = version.get("version").and_then(|v| v.as_str()) {
                         let url = format!("{}/{}.json", base_path, id);
 
-                        unified_versions.push(UnifiedVersion { id, url });
+                        unified_versions.push(UnifiedVersion {
+                            id,
+                            url,
+                            release_type: version.get("type").and_then(|v| v.as_str()),
+                            release_time: version.get("releaseTime").and_then(|v| v.as_str()),
+                            sha1: None,
+                        });
                     }
                 }
 
@@ -75,4 +109,26 @@ impl<'a> UnifiedVersionsData {
 
         Err("Failed to parse".to_string())
     }
+
+    fn extract_mojang(manifest: &'a serde_json::Value) -> Result<Vec<UnifiedVersion<'a>>, String> {
+        let versions = match manifest.get("versions").and_then(|v| v.as_array()) {
+            Some(versions) => versions,
+            None => return Err("Failed to parse".to_string()),
+        };
+
+        let unified_versions = versions
+            .iter()
+            .filter_map(|version| {
+                Some(UnifiedVersion {
+                    id: version.get("id")?.as_str()?,
+                    url: version.get("url")?.as_str()?.to_string(),
+                    release_type: version.get("type").and_then(|v| v.as_str()),
+                    release_time: version.get("releaseTime").and_then(|v| v.as_str()),
+                    sha1: version.get("sha1").and_then(|v| v.as_str()),
+                })
+            })
+            .collect();
+
+        Ok(unified_versions)
+    }
 }
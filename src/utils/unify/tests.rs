@@ -0,0 +1,214 @@
+use std::fs;
+
+use serde_json::json;
+use ts_rs::TS;
+
+use super::{
+    catalog::{CatalogPage, CatalogQuery, CatalogSort, ReleaseType, SortOrder},
+    *,
+};
+
+#[test]
+fn extract_mojang_versions() {
+    let manifest = json!({
+        "latest": { "release": "1.20.1", "snapshot": "23w31a" },
+        "versions": [
+            {
+                "id": "23w31a",
+                "type": "snapshot",
+                "url": "https://piston-meta.mojang.com/v1/packages/a1/23w31a.json",
+                "time": "2023-08-01T11:55:14+00:00",
+                "releaseTime": "2023-08-01T11:46:07+00:00",
+                "sha1": "a1",
+                "complianceLevel": 1
+            },
+            {
+                "id": "rd-132211",
+                "type": "old_alpha",
+                "url": "https://piston-meta.mojang.com/v1/packages/b2/rd-132211.json",
+                "releaseTime": "2009-05-13T20:11:00+00:00",
+                "sha1": "b2"
+            },
+            { "id": "broken" }
+        ]
+    });
+
+    let versions = UnifiedVersionsData::extract_mojang(&manifest).unwrap();
+
+    assert_eq!(
+        versions,
+        vec![
+            UnifiedVersion {
+                id: "23w31a",
+                url: "https://piston-meta.mojang.com/v1/packages/a1/23w31a.json".to_string(),
+                release_type: Some("snapshot"),
+                release_time: Some("2023-08-01T11:46:07+00:00"),
+                sha1: Some("a1"),
+            },
+            UnifiedVersion {
+                id: "rd-132211",
+                url: "https://piston-meta.mojang.com/v1/packages/b2/rd-132211.json".to_string(),
+                release_type: Some("old_alpha"),
+                release_time: Some("2009-05-13T20:11:00+00:00"),
+                sha1: Some("b2"),
+            },
+        ]
+    );
+}
+
+#[test]
+fn extract_mojang_without_versions() {
+    assert!(UnifiedVersionsData::extract_mojang(&json!({})).is_err());
+}
+
+#[test]
+fn extract_prism_versions() {
+    let manifest = json!({
+        "uid": "net.minecraft",
+        "versions": [
+            {
+                "version": "1.20.1",
+                "type": "release",
+                "releaseTime": "2023-06-12T13:25:51+00:00",
+                "sha256": "c3"
+            }
+        ]
+    });
+
+    let versions = UnifiedVersionsData::extract_prism(&manifest).unwrap();
+
+    assert_eq!(
+        versions,
+        vec![UnifiedVersion {
+            id: "1.20.1",
+            url: "https://meta.prismlauncher.org/v1/net.minecraft/1.20.1.json".to_string(),
+            release_type: Some("release"),
+            release_time: Some("2023-06-12T13:25:51+00:00"),
+            sha1: None,
+        }]
+    );
+}
+
+fn catalog() -> Vec<CatalogVersion> {
+    let manifest = json!({
+        "versions": [
+            { "id": "1.20.1", "type": "release", "url": "u", "releaseTime": "2023-06-12T13:25:51+00:00" },
+            { "id": "23w31a", "type": "snapshot", "url": "u", "releaseTime": "2023-08-01T11:46:07+00:00" },
+            { "id": "1.19.4", "type": "release", "url": "u", "releaseTime": "2023-03-14T12:56:18+00:00" },
+            { "id": "1.9", "type": "release", "url": "u", "releaseTime": "2016-02-29T13:49:54+00:00" },
+            { "id": "b1.7.3", "type": "old_beta", "url": "u", "releaseTime": "2011-07-07T22:00:00+00:00" },
+            { "id": "rd-132211", "type": "old_alpha", "url": "u", "releaseTime": "2009-05-13T20:11:00+00:00" }
+        ]
+    });
+
+    UnifiedVersionsData::extract_mojang(&manifest)
+        .unwrap()
+        .into_iter()
+        .map(CatalogVersion::from)
+        .collect()
+}
+
+fn ids(page: &CatalogPage) -> Vec<&str> {
+    page.versions.iter().map(|v| v.id.as_str()).collect()
+}
+
+#[test]
+fn catalog_newest_first_by_default() {
+    let page = CatalogQuery::default().apply(catalog()).unwrap();
+
+    assert_eq!(
+        ids(&page),
+        vec!["23w31a", "1.20.1", "1.19.4", "1.9", "b1.7.3", "rd-132211"]
+    );
+    assert_eq!(page.versions[4].release_type, ReleaseType::Beta);
+}
+
+#[test]
+fn catalog_filters_types_and_dates() {
+    let query = CatalogQuery {
+        types: Some("release,alpha".to_string()),
+        since: Some("2009-01-01".to_string()),
+        until: Some("2023-04-01T00:00:00Z".to_string()),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        ids(&query.apply(catalog()).unwrap()),
+        vec!["1.19.4", "1.9", "rd-132211"]
+    );
+}
+
+#[test]
+fn catalog_until_date_includes_whole_day() {
+    // 1.20.1 is released in the afternoon of the day
+    let query = CatalogQuery {
+        since: Some("2023-06-12".to_string()),
+        until: Some("2023-06-12".to_string()),
+        ..Default::default()
+    };
+
+    assert_eq!(ids(&query.apply(catalog()).unwrap()), vec!["1.20.1"]);
+}
+
+#[test]
+fn catalog_rejects_unknown_type() {
+    let query = CatalogQuery {
+        types: Some("release,modded".to_string()),
+        ..Default::default()
+    };
+
+    assert!(query.apply(catalog()).is_err());
+}
+
+#[test]
+fn catalog_search() {
+    let search = |search: &str| {
+        let query = CatalogQuery {
+            search: Some(search.to_string()),
+            sort: CatalogSort::Id,
+            order: SortOrder::Asc,
+            ..Default::default()
+        };
+        query.apply(catalog()).unwrap()
+    };
+
+    assert_eq!(ids(&search("W31")), vec!["23w31a"]);
+    assert_eq!(ids(&search(">=1.19")), vec!["1.19.4", "1.20.1"]);
+    assert_eq!(ids(&search("<1.10")), vec!["1.9"]);
+    assert_eq!(ids(&search("1.20.x")), vec!["1.20.1"]);
+}
+
+#[test]
+fn catalog_sorts_ids_by_numbers() {
+    let query = CatalogQuery {
+        sort: CatalogSort::Id,
+        order: SortOrder::Asc,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        ids(&query.apply(catalog()).unwrap()),
+        vec!["1.9", "1.19.4", "1.20.1", "23w31a", "b1.7.3", "rd-132211"]
+    );
+}
+
+#[test]
+fn catalog_pagination() {
+    let query = CatalogQuery {
+        page: Some(2),
+        per_page: Some(4),
+        ..Default::default()
+    };
+    let page = query.apply(catalog()).unwrap();
+
+    assert_eq!(ids(&page), vec!["b1.7.3", "rd-132211"]);
+    assert_eq!((page.total, page.pages), (6, 2));
+}
+
+#[test]
+fn export_bindings() -> Result<(), Box<dyn std::error::Error>> {
+    let out = concat!(env!("CARGO_MANIFEST_DIR"), "/bindings/catalog");
+    fs::create_dir_all(out)?;
+    CatalogPage::export_all_to(out)?;
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::{BaseMessage, WsMessage};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(TS)]
+pub struct ConsoleMessage {
+    pub base: BaseMessage,
+    pub data: ConsoleEvent,
+}
+
+impl<'a> From<ConsoleMessage> for WsMessage<'a> {
+    fn from(value: ConsoleMessage) -> Self {
+        WsMessage::Console(value)
+    }
+}
+
+impl From<ConsoleEvent> for ConsoleMessage {
+    fn from(data: ConsoleEvent) -> Self {
+        ConsoleMessage {
+            base: BaseMessage {
+                message_id: String::new(),
+                operation_id: None,
+                request_id: None,
+                timestamp: Utc::now(),
+                correlation_id: None,
+            },
+            data,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", content = "details", rename_all = "snake_case")]
+#[derive(TS)]
+pub enum ConsoleEvent {
+    /// Game process is spawned and supervised
+    Started {
+        instance_id: i64,
+        pid: u32,
+    },
+
+    /// Single line written by the game process
+    Line {
+        instance_id: i64,
+        stream: ConsoleStream,
+        line: String,
+    },
+
+    /// Game process is exited. `signal` is set only on Unix
+    /// when the process was terminated by a signal
+    Exited {
+        instance_id: i64,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        code: Option<i32>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        signal: Option<i32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+#[derive(TS)]
+pub enum ConsoleStream {
+    Stdout,
+    Stderr,
+}
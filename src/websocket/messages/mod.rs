@@ -7,8 +7,9 @@ use thiserror::Error;
 use tide_websockets::WebSocketConnection;
 use ts_rs::TS;
 
-use crate::websocket::messages::{option::OptionUpdateMessage, task::Task};
+use crate::websocket::messages::{console::ConsoleMessage, option::OptionUpdateMessage, task::Task};
 
+pub mod console;
 pub mod operation;
 pub mod scan;
 pub mod task;
@@ -45,7 +46,8 @@ pub enum WsMessage<'a> {
     #[serde(borrow)]
     Task(Task<'a>),
 
-    Option(OptionUpdateMessage)
+    Option(OptionUpdateMessage),
+    Console(ConsoleMessage)
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, TS)]
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::stage::StageError;
+use crate::{
+    instance::{
+        download::{assets::AssetSyncError, libs::LibsSyncError},
+        loader::LoaderError,
+        InstanceError,
+    },
+    utils::download::{retry::Retryable, DownloadError},
+};
+
+/// Kind of the failure, so the client can react without parsing messages
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+#[derive(TS)]
+pub enum ErrorCode {
+    Network,
+    Http,
+    Checksum,
+    Disk,
+    Database,
+    ManifestParse,
+    VersionNotAvailable,
+    UnsupportedOs,
+    UnsupportedArch,
+    InvalidRequest,
+    NotFound,
+    Launch,
+    Cancelled,
+    Internal,
+}
+
+impl StageError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        StageError {
+            code,
+            message: message.into(),
+            target: None,
+            retryable: false,
+            failed_files: Vec::new(),
+        }
+    }
+
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    pub fn retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+}
+
+impl From<&DownloadError> for StageError {
+    fn from(e: &DownloadError) -> Self {
+        let (code, target) = match e {
+            DownloadError::Request(url, _) | DownloadError::Empty(url) => {
+                (ErrorCode::Network, Some(url))
+            }
+            DownloadError::Http(_, url)
+            | DownloadError::TooManyRedirects(url)
+            | DownloadError::NoLocation(_, url) => (ErrorCode::Http, Some(url)),
+            DownloadError::InvalidUrl(_) => (ErrorCode::Http, None),
+            DownloadError::ChecksumMismatch(url) => (ErrorCode::Checksum, Some(url)),
+            DownloadError::Io(_) => (ErrorCode::Disk, None),
+            DownloadError::Cancelled(name) => (ErrorCode::Cancelled, Some(name)),
+        };
+
+        let error = StageError::new(code, e.to_string()).retryable(e.is_retryable());
+        match target {
+            Some(target) => error.with_target(target),
+            None => error,
+        }
+    }
+}
+
+impl From<&LibsSyncError> for StageError {
+    fn from(e: &LibsSyncError) -> Self {
+        match e {
+            LibsSyncError::OsNotAvailable => StageError::new(ErrorCode::UnsupportedOs, e.to_string()),
+            LibsSyncError::ArchNotAvailable => {
+                StageError::new(ErrorCode::UnsupportedArch, e.to_string())
+            }
+            LibsSyncError::Cancelled => StageError::new(ErrorCode::Cancelled, e.to_string()),
+            LibsSyncError::SyncFailed(_) => StageError::new(ErrorCode::Internal, e.to_string()),
+            // Files are requested again on the next init
+            LibsSyncError::DownloadFailed(failed) => StageError {
+                failed_files: failed.clone(),
+                ..StageError::new(ErrorCode::Network, e.to_string()).retryable(true)
+            },
+        }
+    }
+}
+
+impl From<&AssetSyncError> for StageError {
+    fn from(e: &AssetSyncError) -> Self {
+        let code = match e {
+            AssetSyncError::RegisterFailed(_) | AssetSyncError::UnregisterFailed(_) => {
+                ErrorCode::Database
+            }
+            AssetSyncError::Cancelled => ErrorCode::Cancelled,
+            // Assets are downloaded once the launcher is online
+            AssetSyncError::MissingOffline(missing) => {
+                return StageError {
+                    failed_files: missing.clone(),
+                    ..StageError::new(ErrorCode::Network, e.to_string())
+                }
+            }
+        };
+
+        StageError::new(code, e.to_string())
+    }
+}
+
+impl From<&LoaderError> for StageError {
+    fn from(e: &LoaderError) -> Self {
+        match e {
+            LoaderError::VersionNotFound(_) => {
+                StageError::new(ErrorCode::VersionNotAvailable, e.to_string())
+            }
+            LoaderError::MetaFetchFailed(_) | LoaderError::ChecksumFetchFailed(_, _) => {
+                StageError::new(ErrorCode::Network, e.to_string()).retryable(true)
+            }
+            LoaderError::InvalidMeta(_) => StageError::new(ErrorCode::ManifestParse, e.to_string()),
+            LoaderError::NotCached(_) => StageError::new(ErrorCode::Network, e.to_string()),
+            LoaderError::DownloadFailed(url, _) => StageError::new(ErrorCode::Network, e.to_string())
+                .with_target(url.clone())
+                .retryable(true),
+            LoaderError::InvalidInstaller(path, _) => {
+                StageError::new(ErrorCode::ManifestParse, e.to_string()).with_target(path.clone())
+            }
+            LoaderError::ProcessorFailed(jar, _) => {
+                StageError::new(ErrorCode::Internal, e.to_string()).with_target(jar.clone())
+            }
+            // Runtime is installed again on the next init
+            LoaderError::JavaNotAvailable(component) => {
+                StageError::new(ErrorCode::NotFound, e.to_string())
+                    .with_target(component.clone())
+                    .retryable(true)
+            }
+            LoaderError::Cancelled => StageError::new(ErrorCode::Cancelled, e.to_string()),
+        }
+    }
+}
+
+impl From<&InstanceError> for StageError {
+    fn from(e: &InstanceError) -> Self {
+        let code = match e {
+            InstanceError::Libs(e) => return e.into(),
+            InstanceError::Assets(e) => return e.into(),
+            InstanceError::Loader(e) => return e.into(),
+            InstanceError::ManifestFetchFailed(_) => {
+                return StageError::new(ErrorCode::Network, e.to_string()).retryable(true)
+            }
+            InstanceError::InstanceNotFound(_) => ErrorCode::NotFound,
+            InstanceError::RunFailed(_) | InstanceError::Process(_) => ErrorCode::Launch,
+            InstanceError::WrongId(_)
+            | InstanceError::OptionNotAvailable(_)
+            | InstanceError::OptionsPageWrong(_) => ErrorCode::InvalidRequest,
+            InstanceError::RegistrationFailed(_) | InstanceError::DB(_) => ErrorCode::Database,
+            InstanceError::ManifestGenerationFailed(_) => ErrorCode::ManifestParse,
+            InstanceError::DirCreationFailed(_)
+            | InstanceError::HomeDirNotFound
+            | InstanceError::PathsNotInitialized => ErrorCode::Disk,
+            InstanceError::VersionNotAvailable => ErrorCode::VersionNotAvailable,
+            InstanceError::Cancelled => ErrorCode::Cancelled,
+            InstanceError::CreationFailed(_)
+            | InstanceError::VerificationFailed(_)
+            | InstanceError::JSONConstructionFailed(_)
+            | InstanceError::NotImplemented => ErrorCode::Internal,
+        };
+
+        StageError::new(code, e.to_string())
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
-use super::{process::{ProcessStatus, ProcessTarget}, stage::{OperationStage, StageResult}};
+use super::{process::{ProcessStatus, ProcessTarget}, stage::{OperationStage, StageError, StageResult}};
 
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -61,7 +61,7 @@ impl<'a> From<OperationUpdate> for OperationEvent {
 #[derive(TS)]
 pub struct OperationFinish {
     pub status: OperationStatus,
-	// pub error: // TODO: Error
+    pub error: Option<StageError>,
 }
 
 impl<'a> From<OperationFinish> for OperationEvent {
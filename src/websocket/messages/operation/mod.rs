@@ -4,10 +4,13 @@ use ts_rs::TS;
 
 use super::{BaseMessage, WsMessage};
 
+pub mod error;
 pub mod event;
 pub mod stage;
 pub mod process;
 pub mod progress;
+#[cfg(test)]
+mod tests;
 
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -39,4 +42,16 @@ pub enum RequestedTask {
     ScanForInstances {
         scan_range: (u32, u32),
     },
+    StopInstance {
+        instance_id: i64,
+
+        /// Seconds to wait after graceful termination before the kill
+        #[serde(default)]
+        timeout_secs: Option<u64>,
+    },
+    CancelTask {
+        task_id: usize,
+    },
+    /// Look for Java installations on the system
+    ScanJavas,
 }
@@ -56,6 +56,12 @@ pub enum FileStatus {
     Downloading,
     Downloaded,
     FailedToDownload,
+    /// File on disk matches the expected hash
+    Verified,
+    /// Missing or corrupted file was restored
+    Repaired,
+    /// File is missing or doesn't match the expected hash
+    Corrupted,
 }
 
 impl From<FileStatus> for TargetStatus {
@@ -83,7 +89,6 @@ impl ProcessTarget {
         }
     }
 
-    #[allow(dead_code)]
     pub fn file_with_details(
         name: String,
         status: FileStatus,
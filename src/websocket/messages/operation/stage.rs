@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use strum::Display;
 use ts_rs::TS;
 
+use super::error::ErrorCode;
+
 
 #[derive(Serialize, Deserialize, Debug, Clone, Display)]
 #[serde(rename_all = "snake_case")]
@@ -10,6 +12,9 @@ pub enum OperationStage {
     FetchManifest,
     DownloadLibs,
     DownloadAssets,
+    InstallJava,
+    InstallLoader,
+    RunProcessors,
     VerifyFiles,
     CreateStructure,
     ScanInstances
@@ -39,7 +44,21 @@ pub enum StageStatus {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[derive(TS)]
 pub struct StageError {
-	// pub code: ErrorCode,
+    pub code: ErrorCode,
+    pub message: String,
+    /// File, URL or other object the error is related to
+    pub target: Option<String>,
+    /// Whether repeating the operation may succeed
+    pub retryable: bool,
+
+    /// Files which could not be downloaded after all retries
+    pub failed_files: Vec<FailedFile>,
+}
 
-	// TODO: Add fields
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(TS)]
+pub struct FailedFile {
+    pub name: String,
+    pub url: String,
+    pub reason: String,
 }
@@ -0,0 +1,82 @@
+use surf::StatusCode;
+
+use super::{
+    error::ErrorCode,
+    stage::{FailedFile, StageError},
+};
+use crate::{
+    instance::{
+        download::{assets::AssetSyncError, libs::LibsSyncError},
+        loader::LoaderError,
+        InstanceError,
+    },
+    utils::download::DownloadError,
+};
+
+#[test]
+fn checksum_mismatch_is_retryable() {
+    let error = StageError::from(&DownloadError::ChecksumMismatch("https://host/lib.jar".into()));
+
+    assert_eq!(error.code, ErrorCode::Checksum);
+    assert_eq!(error.target.as_deref(), Some("https://host/lib.jar"));
+    assert!(error.retryable);
+}
+
+#[test]
+fn client_http_error_is_not_retryable() {
+    let error = StageError::from(&DownloadError::Http(
+        StatusCode::NotFound,
+        "https://host/lib.jar".into(),
+    ));
+
+    assert_eq!(error.code, ErrorCode::Http);
+    assert!(!error.retryable);
+}
+
+#[test]
+fn failed_libs_are_listed() {
+    let failed = vec![FailedFile {
+        name: "org.lwjgl:lwjgl:3.3.3".into(),
+        url: "https://host/lwjgl.jar".into(),
+        reason: "SHA1 mismatch".into(),
+    }];
+    let error = StageError::from(&InstanceError::Libs(LibsSyncError::DownloadFailed(failed)));
+
+    assert_eq!(error.code, ErrorCode::Network);
+    assert!(error.retryable);
+    assert_eq!(error.failed_files.len(), 1);
+    assert_eq!(error.failed_files[0].name, "org.lwjgl:lwjgl:3.3.3");
+}
+
+#[test]
+fn unsupported_os_from_libs_sync() {
+    let error = StageError::from(&InstanceError::Libs(LibsSyncError::OsNotAvailable));
+
+    assert_eq!(error.code, ErrorCode::UnsupportedOs);
+    assert!(!error.retryable);
+}
+
+#[test]
+fn processors_without_java_are_retryable() {
+    let error = StageError::from(&InstanceError::Loader(LoaderError::JavaNotAvailable(
+        "java-runtime-gamma".into(),
+    )));
+
+    assert_eq!(error.code, ErrorCode::NotFound);
+    assert_eq!(error.target.as_deref(), Some("java-runtime-gamma"));
+    assert!(error.retryable);
+}
+
+#[test]
+fn missing_offline_assets_are_listed() {
+    let missing = vec![FailedFile {
+        name: "minecraft/sounds/ambient/cave/cave1.ogg".into(),
+        url: "https://resources.download.minecraft.net/ab/abcd".into(),
+        reason: "Asset is not downloaded and can't be fetched offline".into(),
+    }];
+    let error = StageError::from(&InstanceError::Assets(AssetSyncError::MissingOffline(missing)));
+
+    assert_eq!(error.code, ErrorCode::Network);
+    assert!(!error.retryable);
+    assert_eq!(error.failed_files.len(), 1);
+}